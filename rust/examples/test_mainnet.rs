@@ -22,25 +22,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("No balance changes found for this bag in this transaction.");
     } else {
         for (index, change) in changes.iter().enumerate() {
-            let prev_formatted = change.previous_value.parse::<f64>().unwrap_or(0.0)
-                / 10_f64.powi(change.decimals as i32);
-            let curr_formatted = change.current_value.parse::<f64>().unwrap_or(0.0)
-                / 10_f64.powi(change.decimals as i32);
-            let diff_formatted = change.value_diff.parse::<f64>().unwrap_or(0.0)
-                / 10_f64.powi(change.decimals as i32);
-
             println!("Change #{}:", index + 1);
             println!("  Coin Type: {}", change.coin_type);
             println!("  Decimals: {}", change.decimals);
             println!(
-                "  Previous Value: {} ({:.9})",
-                change.previous_value, prev_formatted
+                "  Previous Value: {} ({})",
+                change.previous_value,
+                change.previous_amount()
             );
             println!(
-                "  Current Value: {} ({:.9})",
-                change.current_value, curr_formatted
+                "  Current Value: {} ({})",
+                change.current_value,
+                change.current_amount()
             );
-            println!("  Difference: {} ({:.9})", change.value_diff, diff_formatted);
+            println!("  Difference: {} ({})", change.value_diff, change.diff_amount());
             println!();
         }
     }
@@ -0,0 +1,202 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+pub const SUI_ADDRESS_LENGTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("invalid Sui address {0:?}")]
+pub struct AddressParseError(pub String);
+
+/// How addresses are rendered when normalizing parse results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AddressFormat {
+    /// `0x` followed by all 64 hex digits.
+    #[default]
+    Long,
+    /// `0x` followed by the hex digits without leading zeros, e.g. `0x2`.
+    Short,
+}
+
+/// A 32-byte Sui account address. Parsing accepts the short (`0x2`) and
+/// long forms in any letter case, and requires the `0x` prefix; see
+/// [`parse_unprefixed`](Self::parse_unprefixed) for hex without it.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct SuiAddress([u8; SUI_ADDRESS_LENGTH]);
+
+/// Object IDs share the address representation.
+pub type ObjectId = SuiAddress;
+
+impl SuiAddress {
+    pub const ZERO: SuiAddress = SuiAddress([0; SUI_ADDRESS_LENGTH]);
+
+    pub const fn new(bytes: [u8; SUI_ADDRESS_LENGTH]) -> Self {
+        Self(bytes)
+    }
+
+    /// Parses an address with or without the `0x` prefix, as Move's
+    /// `type_name` renders them (`000…002::sui::SUI`).
+    pub fn parse_unprefixed(s: &str) -> Result<Self, AddressParseError> {
+        parse_hex(s.strip_prefix("0x").unwrap_or(s), s)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; SUI_ADDRESS_LENGTH] {
+        &self.0
+    }
+
+    pub fn to_long_string(&self) -> String {
+        let mut out = String::with_capacity(2 + SUI_ADDRESS_LENGTH * 2);
+        out.push_str("0x");
+        for byte in self.0 {
+            out.push_str(&format!("{byte:02x}"));
+        }
+        out
+    }
+
+    pub fn to_short_string(&self) -> String {
+        let long = self.to_long_string();
+        let trimmed = long[2..].trim_start_matches('0');
+        if trimmed.is_empty() {
+            "0x0".to_string()
+        } else {
+            format!("0x{trimmed}")
+        }
+    }
+
+    pub fn format(&self, format: AddressFormat) -> String {
+        match format {
+            AddressFormat::Long => self.to_long_string(),
+            AddressFormat::Short => self.to_short_string(),
+        }
+    }
+}
+
+/// Rewrites `value` in `format` when it is a valid address and leaves it
+/// untouched otherwise (for example `Shared-42` owner labels).
+pub(crate) fn normalize_address(value: &mut String, format: AddressFormat) {
+    if let Ok(address) = value.parse::<SuiAddress>() {
+        *value = address.format(format);
+    }
+}
+
+/// Compares two address strings by value, falling back to string equality
+/// when either side is not a valid address.
+pub(crate) fn same_address(left: &str, right: &str) -> bool {
+    match (left.parse::<SuiAddress>(), right.parse::<SuiAddress>()) {
+        (Ok(left), Ok(right)) => left == right,
+        _ => left == right,
+    }
+}
+
+impl FromStr for SuiAddress {
+    type Err = AddressParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix("0x") {
+            Some(hex) => parse_hex(hex, s),
+            None => Err(AddressParseError(s.to_string())),
+        }
+    }
+}
+
+/// Parses the hex digits of an address; `input` is reported on errors.
+fn parse_hex(hex: &str, input: &str) -> Result<SuiAddress, AddressParseError> {
+    if hex.is_empty()
+        || hex.len() > SUI_ADDRESS_LENGTH * 2
+        || !hex.bytes().all(|b| b.is_ascii_hexdigit())
+    {
+        return Err(AddressParseError(input.to_string()));
+    }
+
+    let padded = format!("{hex:0>width$}", width = SUI_ADDRESS_LENGTH * 2);
+    let mut bytes = [0; SUI_ADDRESS_LENGTH];
+    for (index, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&padded[index * 2..index * 2 + 2], 16)
+            .map_err(|_| AddressParseError(input.to_string()))?;
+    }
+
+    Ok(SuiAddress(bytes))
+}
+
+impl fmt::Display for SuiAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_long_string())
+    }
+}
+
+impl fmt::Debug for SuiAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SuiAddress({self})")
+    }
+}
+
+impl Serialize for SuiAddress {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for SuiAddress {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        raw.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LONG_TWO: &str = "0x0000000000000000000000000000000000000000000000000000000000000002";
+
+    #[test]
+    fn parses_short_and_long_forms() {
+        let short: SuiAddress = "0x2".parse().expect("short form should parse");
+        let long: SuiAddress = LONG_TWO.parse().expect("long form should parse");
+        let bare = SuiAddress::parse_unprefixed("2").expect("missing prefix should parse");
+
+        assert_eq!(short, long);
+        assert_eq!(short, bare);
+        assert_eq!(SuiAddress::parse_unprefixed("0x2"), Ok(short));
+        assert_eq!(short.to_long_string(), LONG_TWO);
+        assert_eq!(long.to_short_string(), "0x2");
+        assert_eq!(SuiAddress::ZERO.to_short_string(), "0x0");
+        assert_eq!(
+            "0x6F4D3A"
+                .parse::<SuiAddress>()
+                .map(|address| address.format(AddressFormat::Short)),
+            Ok("0x6f4d3a".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_addresses() {
+        assert!("".parse::<SuiAddress>().is_err());
+        assert!("0x".parse::<SuiAddress>().is_err());
+        assert!("0xg1".parse::<SuiAddress>().is_err());
+        assert!("2".parse::<SuiAddress>().is_err());
+        assert!(format!("0x{}", "1".repeat(65))
+            .parse::<SuiAddress>()
+            .is_err());
+    }
+
+    #[test]
+    fn normalizes_strings_in_place() {
+        let mut owner = "0x02".to_string();
+        normalize_address(&mut owner, AddressFormat::Long);
+        assert_eq!(owner, LONG_TWO);
+
+        let mut shared = "Shared-42".to_string();
+        normalize_address(&mut shared, AddressFormat::Long);
+        assert_eq!(shared, "Shared-42");
+
+        let mut digest = "abc".to_string();
+        normalize_address(&mut digest, AddressFormat::Long);
+        assert_eq!(digest, "abc");
+
+        assert!(same_address("0x2", LONG_TWO));
+        assert!(!same_address("0x2", "0x3"));
+    }
+}
@@ -0,0 +1,383 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::address::{normalize_address, AddressFormat};
+use crate::type_tag::{normalize_type, TypeTag};
+use crate::types::ParseResult;
+use crate::utils::{is_sui_coin_type, string_number};
+
+/// Net balance change per owner, then per coin type. Addresses in both keys
+/// are in [`AddressFormat::Short`], so `0x2::sui::SUI` finds SUI however the
+/// node wrote it.
+pub type OwnerFlows = BTreeMap<String, BTreeMap<String, i128>>;
+
+/// Totals of the balance changes of one coin type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoinFlow {
+    /// Sum of the positive changes.
+    #[serde(with = "string_number")]
+    pub inflow: i128,
+    /// Sum of the negative changes, as a positive number.
+    #[serde(with = "string_number")]
+    pub outflow: i128,
+    #[serde(with = "string_number")]
+    pub net: i128,
+}
+
+impl CoinFlow {
+    fn add(&mut self, amount: i128) {
+        if amount >= 0 {
+            self.inflow += amount;
+        } else {
+            self.outflow -= amount;
+        }
+        self.net += amount;
+    }
+}
+
+/// A coin type whose `Coin` objects in one transaction do not net to zero,
+/// or to minus the net gas fee for SUI. Also expected when value moved into
+/// or out of a `Balance` held by another object; see
+/// [`ParseResult::coin_object_imbalances`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Imbalance {
+    pub coin_type: String,
+    /// Sum of all balance changes of the coin type.
+    #[serde(with = "string_number")]
+    pub net: i128,
+    /// What `net` should have been.
+    #[serde(with = "string_number")]
+    pub expected: i128,
+}
+
+impl ParseResult {
+    pub fn net_flows_by_owner(&self) -> OwnerFlows {
+        let mut flows = OwnerFlows::new();
+        add_owner_flows(&mut flows, self);
+        flows
+    }
+
+    pub fn net_flows_by_coin(&self) -> BTreeMap<String, CoinFlow> {
+        let mut flows = BTreeMap::new();
+        add_coin_flows(&mut flows, self);
+        flows
+    }
+
+    /// Coin types whose balance changes do not sum to zero, or to minus the
+    /// net gas fee for SUI. Coin types whose `TreasuryCap` or `Supply` was
+    /// touched are skipped as minted or burned; this needs
+    /// [`ParseOptions::object_changes`](crate::ParseOptions::object_changes),
+    /// otherwise every mint and burn is reported.
+    ///
+    /// Balance changes only cover `Coin` objects, so this is not a check
+    /// that value was conserved. A swap, stake or deposit moves coins into
+    /// a `Balance` inside a pool or vault, and is reported here for every
+    /// coin it moves.
+    /// [`get_container_balance_report`](crate::TxParseClient::get_container_balance_report)
+    /// can trace the object side.
+    pub fn coin_object_imbalances(&self) -> Vec<Imbalance> {
+        self.net_flows_by_coin()
+            .into_iter()
+            .filter_map(|(coin_type, flow)| {
+                let expected = if is_sui_coin_type(&coin_type) {
+                    -self.net_gas_fee
+                } else {
+                    0
+                };
+                (flow.net != expected && !self.changes_supply_of(&coin_type)).then_some(Imbalance {
+                    coin_type,
+                    net: flow.net,
+                    expected,
+                })
+            })
+            .collect()
+    }
+
+    fn changes_supply_of(&self, coin_type: &str) -> bool {
+        let Ok(coin) = coin_type.parse::<TypeTag>() else {
+            return false;
+        };
+        self.object_changes
+            .iter()
+            .flatten()
+            .filter_map(|change| change.object_type()?.parse::<TypeTag>().ok())
+            .any(|object_type| holds_supply_of(&object_type, &coin))
+    }
+}
+
+/// Flows summed over many transactions, e.g. a block range or an account
+/// history.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BalanceSummary {
+    transactions: usize,
+    owners: OwnerFlows,
+    coins: BTreeMap<String, CoinFlow>,
+    imbalances: Vec<(usize, Imbalance)>,
+}
+
+impl BalanceSummary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, parsed: &ParseResult) {
+        add_owner_flows(&mut self.owners, parsed);
+        add_coin_flows(&mut self.coins, parsed);
+        let index = self.transactions;
+        self.imbalances.extend(
+            parsed
+                .coin_object_imbalances()
+                .into_iter()
+                .map(|imbalance| (index, imbalance)),
+        );
+        self.transactions += 1;
+    }
+
+    /// Number of transactions added.
+    pub fn transactions(&self) -> usize {
+        self.transactions
+    }
+
+    pub fn by_owner(&self) -> &OwnerFlows {
+        &self.owners
+    }
+
+    pub fn by_coin(&self) -> &BTreeMap<String, CoinFlow> {
+        &self.coins
+    }
+
+    /// [`ParseResult::coin_object_imbalances`] of each transaction, paired
+    /// with the position in which the transaction was added. Checked per
+    /// transaction so that a mint in one cannot hide a missing amount in
+    /// another.
+    pub fn coin_object_imbalances(&self) -> &[(usize, Imbalance)] {
+        &self.imbalances
+    }
+}
+
+impl<'a> Extend<&'a ParseResult> for BalanceSummary {
+    fn extend<I: IntoIterator<Item = &'a ParseResult>>(&mut self, results: I) {
+        for parsed in results {
+            self.add(parsed);
+        }
+    }
+}
+
+impl<'a> FromIterator<&'a ParseResult> for BalanceSummary {
+    fn from_iter<I: IntoIterator<Item = &'a ParseResult>>(results: I) -> Self {
+        let mut summary = Self::new();
+        summary.extend(results);
+        summary
+    }
+}
+
+fn add_owner_flows(flows: &mut OwnerFlows, parsed: &ParseResult) {
+    for change in &parsed.balance_changes {
+        *flows
+            .entry(owner_key(&change.owner))
+            .or_default()
+            .entry(coin_key(&change.coin_type))
+            .or_default() += change.amount;
+    }
+}
+
+fn add_coin_flows(flows: &mut BTreeMap<String, CoinFlow>, parsed: &ParseResult) {
+    for change in &parsed.balance_changes {
+        flows
+            .entry(coin_key(&change.coin_type))
+            .or_default()
+            .add(change.amount);
+    }
+}
+
+fn owner_key(owner: &str) -> String {
+    let mut key = owner.to_string();
+    normalize_address(&mut key, AddressFormat::Short);
+    key
+}
+
+fn coin_key(coin_type: &str) -> String {
+    let mut key = coin_type.to_string();
+    normalize_type(&mut key, AddressFormat::Short);
+    key
+}
+
+/// Whether `object_type` is or contains the `TreasuryCap<T>` or `Supply<T>`
+/// of `coin`.
+fn holds_supply_of(object_type: &TypeTag, coin: &TypeTag) -> bool {
+    let Some(tag) = object_type.as_struct() else {
+        return false;
+    };
+    let is_supply = tag.is("0x2", "coin", "TreasuryCap") || tag.is("0x2", "balance", "Supply");
+    (is_supply && tag.type_params.first() == Some(coin))
+        || tag
+            .type_params
+            .iter()
+            .any(|param| holds_supply_of(param, coin))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_transaction_value;
+    use crate::types::{BalanceChange, GasCostSummary, ObjectChange};
+    use serde_json::Value;
+
+    fn change(owner: &str, coin_type: &str, amount: i128) -> BalanceChange {
+        BalanceChange {
+            coin_type: coin_type.to_string(),
+            amount,
+            owner: owner.to_string(),
+            owner_details: None,
+            coin_metadata: None,
+            valuation: None,
+        }
+    }
+
+    fn transaction(changes: Vec<BalanceChange>, net_gas_fee: i128) -> ParseResult {
+        ParseResult {
+            balance_changes: changes,
+            transfer_balance_changes: None,
+            gas_cost: GasCostSummary {
+                computation_cost: 0,
+                storage_cost: 0,
+                storage_rebate: 0,
+                non_refundable_storage_fee: 0,
+            },
+            events: Vec::new(),
+            transaction: None,
+            status: None,
+            sender: None,
+            gas_data: None,
+            object_changes: None,
+            net_gas_fee,
+            timestamp_ms: None,
+            gas_fee_valuation: None,
+        }
+    }
+
+    const SUI: &str = "0x2::sui::SUI";
+    const USDC: &str = "0xdba3::usdc::USDC";
+
+    #[test]
+    fn nets_flows_by_owner_and_coin() {
+        let parsed = transaction(
+            vec![
+                change("0xa", SUI, -1_250),
+                change("0xb", SUI, 1_000),
+                change("0xa", USDC, 500),
+                change("0xb", USDC, -500),
+            ],
+            250,
+        );
+
+        let owners = parsed.net_flows_by_owner();
+        assert_eq!(owners["0xa"][SUI], -1_250);
+        assert_eq!(owners["0xb"][USDC], -500);
+
+        let coins = parsed.net_flows_by_coin();
+        assert_eq!(
+            coins[SUI],
+            CoinFlow {
+                inflow: 1_000,
+                outflow: 1_250,
+                net: -250,
+            }
+        );
+        assert_eq!(coins[USDC].net, 0);
+        assert!(parsed.coin_object_imbalances().is_empty());
+    }
+
+    #[test]
+    fn merges_short_and_long_address_forms() {
+        let long_sui =
+            "0x0000000000000000000000000000000000000000000000000000000000000002::sui::SUI";
+        let long_owner = "0x000000000000000000000000000000000000000000000000000000000000000a";
+        let parsed = transaction(
+            vec![
+                change("0xa", SUI, -1_010),
+                change(long_owner, long_sui, 0),
+                change("0x0b", long_sui, 1_000),
+            ],
+            10,
+        );
+
+        let summary: BalanceSummary = [&parsed].into_iter().collect();
+        assert_eq!(
+            summary.by_owner().keys().collect::<Vec<_>>(),
+            vec!["0xa", "0xb"]
+        );
+        assert_eq!(summary.by_owner()["0xa"][SUI], -1_010);
+        assert_eq!(summary.by_coin().len(), 1);
+        assert_eq!(summary.by_coin()[SUI].net, -10);
+        assert!(summary.coin_object_imbalances().is_empty());
+    }
+
+    #[test]
+    fn flags_unbalanced_coins_unless_their_supply_changed() {
+        let mut minted = transaction(vec![change("0xa", USDC, 700), change("0xa", SUI, -10)], 10);
+        assert_eq!(
+            minted.coin_object_imbalances(),
+            vec![Imbalance {
+                coin_type: USDC.to_string(),
+                net: 700,
+                expected: 0,
+            }]
+        );
+
+        minted.object_changes = Some(vec![ObjectChange::Mutated {
+            object_id: "0xcap".to_string(),
+            object_type: format!("0x2::coin::TreasuryCap<{USDC}>"),
+            owner: None,
+            version: 3,
+            previous_version: 2,
+            digest: "CAPDIGEST".to_string(),
+        }]);
+        assert!(minted.coin_object_imbalances().is_empty());
+
+        let leaked = transaction(vec![change("0xa", SUI, -15), change("0xb", SUI, 4)], 10);
+        let summary: BalanceSummary = [&minted, &leaked].into_iter().collect();
+        assert_eq!(summary.transactions(), 2);
+        assert_eq!(summary.by_owner()["0xa"][SUI], -25);
+        assert_eq!(summary.by_coin()[USDC].inflow, 700);
+        assert_eq!(
+            summary.coin_object_imbalances(),
+            &[(
+                1,
+                Imbalance {
+                    coin_type: SUI.to_string(),
+                    net: -11,
+                    expected: -10,
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn reports_coins_moved_into_pools_as_imbalances() {
+        let raw = include_str!("../../fixtures/swap_transaction_block.json");
+        let value: Value = serde_json::from_str(raw).expect("fixture should parse");
+        let swap = parse_transaction_value(&value).expect("fixture should be valid");
+
+        // The SUI paid in and the USDC paid out sit in the pool's `Balance`
+        // fields, which balance changes do not cover.
+        assert_eq!(swap.net_gas_fee, 1_029_640);
+        assert_eq!(
+            swap.coin_object_imbalances(),
+            vec![
+                Imbalance {
+                    coin_type: SUI.to_string(),
+                    net: -1_001_029_640,
+                    expected: -1_029_640,
+                },
+                Imbalance {
+                    coin_type: USDC.to_string(),
+                    net: 2_100_000,
+                    expected: 0,
+                },
+            ]
+        );
+    }
+}
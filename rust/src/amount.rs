@@ -0,0 +1,277 @@
+use num_bigint::{BigInt, Sign};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// How [`CoinAmount::round_dp`] resolves digits that are dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoundingMode {
+    /// Toward zero.
+    Down,
+    /// Away from zero.
+    Up,
+    /// Toward negative infinity.
+    Floor,
+    /// Toward positive infinity.
+    Ceiling,
+    /// To the nearest value, ties away from zero.
+    HalfUp,
+    /// To the nearest value, ties to the even neighbour.
+    #[default]
+    HalfEven,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum AmountParseError {
+    #[error("invalid amount {0:?}")]
+    Invalid(String),
+    #[error("amount {amount:?} has more than {decimals} decimal places")]
+    TooPrecise { amount: String, decimals: u8 },
+}
+
+/// An exact fixed-point coin amount: a raw integer in the coin's smallest
+/// unit together with the coin's decimals.
+///
+/// Displays and serializes with all `decimals` fractional digits, e.g.
+/// `1370791` with 9 decimals is `0.001370791`, so the string form round
+/// trips without loss. A formatting precision such as `{:.2}` rounds with
+/// [`RoundingMode::HalfEven`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CoinAmount {
+    raw: BigInt,
+    decimals: u8,
+}
+
+impl CoinAmount {
+    pub fn new(raw: impl Into<BigInt>, decimals: u8) -> Self {
+        Self {
+            raw: raw.into(),
+            decimals,
+        }
+    }
+
+    /// Parses a human amount such as `"1.5"` into a coin with `decimals`,
+    /// failing rather than rounding when it has more decimal places.
+    pub fn parse_with_decimals(amount: &str, decimals: u8) -> Result<Self, AmountParseError> {
+        let parsed: CoinAmount = amount.parse()?;
+        if parsed.decimals > decimals {
+            return Err(AmountParseError::TooPrecise {
+                amount: amount.to_string(),
+                decimals,
+            });
+        }
+        Ok(Self {
+            raw: parsed.raw * pow10(decimals - parsed.decimals),
+            decimals,
+        })
+    }
+
+    /// The amount in the coin's smallest unit.
+    pub fn raw(&self) -> &BigInt {
+        &self.raw
+    }
+
+    pub fn decimals(&self) -> u8 {
+        self.decimals
+    }
+
+    /// The exact product, e.g. of an amount and a unit price. Decimals add
+    /// up; beyond 255 in total, `factor` is rounded half-even first.
+    pub fn multiply(&self, factor: &CoinAmount) -> CoinAmount {
+        let factor = factor.round_dp(u8::MAX - self.decimals, RoundingMode::HalfEven);
+        CoinAmount {
+            raw: &self.raw * &factor.raw,
+            decimals: self.decimals + factor.decimals,
+        }
+    }
+
+    /// Rounds to at most `dp` decimal places. Amounts that already have no
+    /// more than `dp` are returned unchanged.
+    pub fn round_dp(&self, dp: u8, mode: RoundingMode) -> CoinAmount {
+        if dp >= self.decimals {
+            return self.clone();
+        }
+
+        let scale = pow10(self.decimals - dp);
+        let quotient = &self.raw / &scale;
+        let remainder = &self.raw % &scale;
+        let away_from_zero = if remainder.sign() == Sign::NoSign {
+            false
+        } else {
+            let half = (remainder.magnitude() * 2u8).cmp(scale.magnitude());
+            match mode {
+                RoundingMode::Down => false,
+                RoundingMode::Up => true,
+                RoundingMode::Floor => self.raw.sign() == Sign::Minus,
+                RoundingMode::Ceiling => self.raw.sign() == Sign::Plus,
+                RoundingMode::HalfUp => half.is_ge(),
+                RoundingMode::HalfEven => half.is_gt() || (half.is_eq() && quotient.bit(0)),
+            }
+        };
+
+        let raw = if !away_from_zero {
+            quotient
+        } else if self.raw.sign() == Sign::Minus {
+            quotient - 1
+        } else {
+            quotient + 1
+        };
+        CoinAmount { raw, decimals: dp }
+    }
+}
+
+impl fmt::Display for CoinAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rounded;
+        let (amount, padding) = match f.precision() {
+            Some(precision) if precision < self.decimals as usize => {
+                rounded = self.round_dp(precision as u8, RoundingMode::HalfEven);
+                (&rounded, 0)
+            }
+            Some(precision) => (self, precision - self.decimals as usize),
+            None => (self, 0),
+        };
+
+        let decimals = amount.decimals as usize;
+        let digits = amount.raw.magnitude().to_string();
+        let digits = format!("{digits:0>width$}", width = decimals + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - decimals);
+
+        if amount.raw.sign() == Sign::Minus {
+            f.write_str("-")?;
+        }
+        f.write_str(whole)?;
+        if decimals + padding > 0 {
+            write!(f, ".{fraction}{:0<padding$}", "")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a decimal string, taking the decimals from the number of
+/// fractional digits: `"1.50"` has 2.
+impl FromStr for CoinAmount {
+    type Err = AmountParseError;
+
+    fn from_str(amount: &str) -> Result<Self, Self::Err> {
+        let invalid = || AmountParseError::Invalid(amount.to_string());
+        let (negative, unsigned) = match amount.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, amount.strip_prefix('+').unwrap_or(amount)),
+        };
+        let (whole, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+        let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if whole.is_empty()
+            || !is_digits(whole)
+            || !is_digits(fraction)
+            || (fraction.is_empty() && unsigned.ends_with('.'))
+        {
+            return Err(invalid());
+        }
+        let decimals = u8::try_from(fraction.len()).map_err(|_| AmountParseError::TooPrecise {
+            amount: amount.to_string(),
+            decimals: u8::MAX,
+        })?;
+
+        let mut raw: BigInt = format!("{whole}{fraction}")
+            .parse()
+            .map_err(|_| invalid())?;
+        if negative {
+            raw = -raw;
+        }
+        Ok(Self { raw, decimals })
+    }
+}
+
+impl Serialize for CoinAmount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for CoinAmount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+fn pow10(exponent: u8) -> BigInt {
+    BigInt::from(10u8).pow(exponent as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn displays_and_parses_without_loss() {
+        let amount = CoinAmount::new(1_370_791, 9);
+        assert_eq!(amount.to_string(), "0.001370791");
+        assert_eq!(
+            CoinAmount::new(-4_327_310_680_948i64, 9).to_string(),
+            "-4327.310680948"
+        );
+        assert_eq!(CoinAmount::new(5, 0).to_string(), "5");
+
+        let large: BigInt = "123456789012345678901234567890".parse().unwrap();
+        let amount = CoinAmount::new(large.clone(), 18);
+        assert_eq!(amount.to_string(), "123456789012.345678901234567890");
+        assert_eq!(amount.to_string().parse::<CoinAmount>(), Ok(amount.clone()));
+
+        let json = serde_json::to_string(&amount).expect("amount should serialize");
+        assert_eq!(json, "\"123456789012.345678901234567890\"");
+        let back: CoinAmount = serde_json::from_str(&json).expect("amount should deserialize");
+        assert_eq!(back.raw(), &large);
+    }
+
+    #[test]
+    fn rounds_with_each_mode() {
+        let cases = [
+            (RoundingMode::Down, "1.23", "-1.23"),
+            (RoundingMode::Up, "1.24", "-1.24"),
+            (RoundingMode::Floor, "1.23", "-1.24"),
+            (RoundingMode::Ceiling, "1.24", "-1.23"),
+            (RoundingMode::HalfUp, "1.24", "-1.24"),
+            (RoundingMode::HalfEven, "1.24", "-1.24"),
+        ];
+        for (mode, positive, negative) in cases {
+            let amount = CoinAmount::new(12_350, 4);
+            assert_eq!(amount.round_dp(2, mode).to_string(), positive, "{mode:?}");
+            let amount = CoinAmount::new(-12_350, 4);
+            assert_eq!(amount.round_dp(2, mode).to_string(), negative, "{mode:?}");
+        }
+
+        let tie_to_even = CoinAmount::new(12_250, 4).round_dp(2, RoundingMode::HalfEven);
+        assert_eq!(tie_to_even.to_string(), "1.22");
+        assert_eq!(format!("{:.2}", CoinAmount::new(1_226, 3)), "1.23");
+        assert_eq!(format!("{:.4}", CoinAmount::new(15, 1)), "1.5000");
+
+        let value = CoinAmount::new(-750, 3).multiply(&CoinAmount::new(125, 2));
+        assert_eq!(value.to_string(), "-0.93750");
+    }
+
+    #[test]
+    fn parses_human_amounts_for_a_coin() {
+        let amount = CoinAmount::parse_with_decimals("1.5", 6).expect("amount should parse");
+        assert_eq!(amount.raw(), &BigInt::from(1_500_000));
+        assert_eq!(amount.decimals(), 6);
+        assert_eq!(
+            CoinAmount::parse_with_decimals("-2", 9).map(|amount| amount.to_string()),
+            Ok("-2.000000000".to_string())
+        );
+
+        assert_eq!(
+            CoinAmount::parse_with_decimals("0.0000001", 6),
+            Err(AmountParseError::TooPrecise {
+                amount: "0.0000001".to_string(),
+                decimals: 6,
+            })
+        );
+        for invalid in ["", "-", ".5", "1.", "1.2.3", "1e9", "1,000"] {
+            assert!(invalid.parse::<CoinAmount>().is_err(), "{invalid:?}");
+        }
+    }
+}
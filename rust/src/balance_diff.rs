@@ -0,0 +1,2011 @@
+use num_bigint::{BigInt, BigUint};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::address::same_address;
+use crate::client::{ClientError, TxParseClient};
+use crate::type_tag::{find_rendering, TypeTag};
+use crate::types::{
+    DynamicFieldBalanceChange, DynamicFieldBalanceReport, ObjectBalanceChange,
+    ObjectChangesResponse, ObjectDetails, ObjectFailure, ObjectFailureReason, ObjectOwner,
+    PastObjectResponse, RawNormalizedField, RawNormalizedStruct, RawObjectChange,
+};
+
+/// Maximum number of objects in one `sui_tryMultiGetPastObjects` or
+/// `sui_multiGetObjects` call.
+const MAX_MULTI_GET_OBJECTS: usize = 50;
+
+/// Object versions loaded for one transaction, keyed by object ID and version.
+type VersionStore = HashMap<(String, String), Result<PastObjectResponse, ObjectFailureReason>>;
+
+/// A changed object that may hold part of a container's balance.
+struct FieldCandidate {
+    /// Object whose content holds the balance: the `Field` itself for `Bag`
+    /// and `Table`, the value object for `ObjectBag`, `ObjectTable` and other
+    /// dynamic object fields.
+    object_id: String,
+    /// Version after the transaction, `None` when the field was removed.
+    version: Option<String>,
+    previous_version: Option<String>,
+    /// Key of a dynamic object field, read from its parent `Field`. Plain
+    /// fields carry the key in their own content.
+    field_name: Option<Value>,
+}
+
+/// A coin-holding object owned by another object, which may be the `Field`
+/// wrapper of a dynamic object field on the container.
+struct ChildObject {
+    object_id: String,
+    version: String,
+    previous_version: Option<String>,
+    parent_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawObjectResponse {
+    data: Option<ObjectDetails>,
+}
+
+impl TxParseClient {
+    /// Balance changes of the bag's dynamic fields. Objects that could not be
+    /// processed are left out; use
+    /// [`get_bag_dynamic_field_balance_report`](Self::get_bag_dynamic_field_balance_report)
+    /// to list them, or
+    /// [`get_bag_dynamic_field_balance_changes_strict`](Self::get_bag_dynamic_field_balance_changes_strict)
+    /// to fail on them.
+    pub async fn get_bag_dynamic_field_balance_changes(
+        &self,
+        tx_digest: &str,
+        bag_id: &str,
+    ) -> Result<Vec<DynamicFieldBalanceChange>, ClientError> {
+        self.get_container_balance_changes(tx_digest, bag_id).await
+    }
+
+    /// Like [`get_bag_dynamic_field_balance_changes`], but fails with
+    /// [`ClientError::IncompleteBalanceData`] unless every bag-owned object
+    /// could be processed.
+    ///
+    /// [`get_bag_dynamic_field_balance_changes`]: Self::get_bag_dynamic_field_balance_changes
+    pub async fn get_bag_dynamic_field_balance_changes_strict(
+        &self,
+        tx_digest: &str,
+        bag_id: &str,
+    ) -> Result<Vec<DynamicFieldBalanceChange>, ClientError> {
+        self.get_container_balance_changes_strict(tx_digest, bag_id)
+            .await
+    }
+
+    /// Balance changes of the bag's dynamic fields, listing objects that could
+    /// not be processed instead of failing the whole call.
+    pub async fn get_bag_dynamic_field_balance_report(
+        &self,
+        tx_digest: &str,
+        bag_id: &str,
+    ) -> Result<DynamicFieldBalanceReport, ClientError> {
+        self.get_container_balance_report(tx_digest, bag_id).await
+    }
+
+    /// [`get_container_balance_report`] without the objects that could not be
+    /// processed.
+    ///
+    /// [`get_container_balance_report`]: Self::get_container_balance_report
+    pub async fn get_container_balance_changes(
+        &self,
+        tx_digest: &str,
+        container_id: &str,
+    ) -> Result<Vec<DynamicFieldBalanceChange>, ClientError> {
+        let report = self
+            .get_container_balance_report(tx_digest, container_id)
+            .await?;
+        Ok(report.changes)
+    }
+
+    /// Strict variant of [`get_container_balance_report`]: fails with
+    /// [`ClientError::IncompleteBalanceData`] unless every object could be
+    /// processed.
+    ///
+    /// [`get_container_balance_report`]: Self::get_container_balance_report
+    pub async fn get_container_balance_changes_strict(
+        &self,
+        tx_digest: &str,
+        container_id: &str,
+    ) -> Result<Vec<DynamicFieldBalanceChange>, ClientError> {
+        let report = self
+            .get_container_balance_report(tx_digest, container_id)
+            .await?;
+        if report.is_complete() {
+            Ok(report.changes)
+        } else {
+            Err(ClientError::IncompleteBalanceData(report.failures))
+        }
+    }
+
+    /// Balance changes of the dynamic fields under `container_id`, which may
+    /// be a `Bag`, `Table`, `ObjectBag`, `ObjectTable` or the UID of any
+    /// object with dynamic (object) fields. Values stored through
+    /// `dynamic_object_field` are followed through their `Field` wrapper;
+    /// a wrapper the transaction did not change is read at its latest
+    /// version, so one removed since is reported as
+    /// [`ObjectFailureReason::UnresolvedOwner`].
+    pub async fn get_container_balance_report(
+        &self,
+        tx_digest: &str,
+        container_id: &str,
+    ) -> Result<DynamicFieldBalanceReport, ClientError> {
+        let result: ObjectChangesResponse = self
+            .call(
+                "sui_getTransactionBlock",
+                json!([tx_digest, { "showObjectChanges": true, "showEffects": true }]),
+            )
+            .await?;
+        let object_changes = result.object_changes.unwrap_or_default();
+        let modified_at_versions: HashMap<&str, &str> = result
+            .effects
+            .iter()
+            .flat_map(|effects| &effects.modified_at_versions)
+            .map(|modified| {
+                (
+                    modified.object_id.as_str(),
+                    modified.sequence_number.as_str(),
+                )
+            })
+            .collect();
+
+        let mut report = DynamicFieldBalanceReport::default();
+        let mut candidates = Vec::new();
+        let mut children = Vec::new();
+        let mut removed_fields = Vec::new();
+
+        for change in &object_changes {
+            match change {
+                RawObjectChange::Created {
+                    object_id,
+                    object_type,
+                    version,
+                    owner,
+                    ..
+                }
+                | RawObjectChange::Mutated {
+                    object_id,
+                    object_type,
+                    version,
+                    owner,
+                    ..
+                } => {
+                    let Some(ObjectOwner::ObjectOwner { object_owner }) = owner else {
+                        continue;
+                    };
+                    let previous_version = match change {
+                        RawObjectChange::Mutated {
+                            previous_version, ..
+                        } => Some(previous_version.clone()),
+                        _ => None,
+                    };
+
+                    if same_address(object_owner, container_id) {
+                        candidates.push(FieldCandidate {
+                            object_id: object_id.clone(),
+                            version: Some(version.clone()),
+                            previous_version,
+                            field_name: None,
+                        });
+                    } else if holds_coin(object_type) && !is_dynamic_field(object_type) {
+                        // A `Field` is a plain dynamic field of some UID and
+                        // never the value of a dynamic object field.
+                        children.push(ChildObject {
+                            object_id: object_id.clone(),
+                            version: version.clone(),
+                            previous_version,
+                            parent_id: object_owner.clone(),
+                        });
+                    }
+                }
+                // Deleted and wrapped fields carry no owner, so take their
+                // input version from the effects and check ownership once it
+                // is loaded.
+                RawObjectChange::Deleted {
+                    object_id,
+                    object_type,
+                    ..
+                }
+                | RawObjectChange::Wrapped {
+                    object_id,
+                    object_type,
+                    ..
+                } if is_dynamic_field(object_type) => {
+                    match modified_at_versions.get(object_id.as_str()) {
+                        Some(previous_version) => {
+                            removed_fields.push((object_id.clone(), previous_version.to_string()))
+                        }
+                        None => report.failures.push(ObjectFailure {
+                            object_id: object_id.clone(),
+                            reason: ObjectFailureReason::MissingInputVersion,
+                        }),
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Resolve the parents of coin-holding children: those that are
+        // dynamic object field wrappers on the container contribute their
+        // value object. Parents changed by the transaction are checked against
+        // their change and read at that version. Other parents are read at
+        // their latest version, which is accurate for as long as a wrapper
+        // exists since its key and owner never change; a wrapper removed since
+        // cannot be told apart from any other missing object and is reported.
+        let changed_parents: HashMap<&str, (&str, &str, Option<&ObjectOwner>)> = object_changes
+            .iter()
+            .filter_map(|change| match change {
+                RawObjectChange::Created {
+                    object_id,
+                    object_type,
+                    version,
+                    owner,
+                    ..
+                }
+                | RawObjectChange::Mutated {
+                    object_id,
+                    object_type,
+                    version,
+                    owner,
+                    ..
+                } => Some((
+                    object_id.as_str(),
+                    (version.as_str(), object_type.as_str(), owner.as_ref()),
+                )),
+                _ => None,
+            })
+            .collect();
+        let mut parent_versions = Vec::new();
+        let mut parent_ids = Vec::new();
+        children.retain(
+            |child| match changed_parents.get(child.parent_id.as_str()) {
+                Some(&(version, object_type, owner)) => {
+                    let plausible = is_object_field_wrapper(object_type)
+                        && matches!(
+                            owner,
+                            Some(ObjectOwner::ObjectOwner { object_owner })
+                                if same_address(object_owner, container_id)
+                        );
+                    let key = (child.parent_id.clone(), version.to_string());
+                    if plausible && !parent_versions.contains(&key) {
+                        parent_versions.push(key);
+                    }
+                    plausible
+                }
+                None => {
+                    if !parent_ids.contains(&child.parent_id) {
+                        parent_ids.push(child.parent_id.clone());
+                    }
+                    true
+                }
+            },
+        );
+        let mut store = VersionStore::new();
+        self.load_versions(parent_versions, &mut store).await;
+        let latest = self.get_latest_objects(&parent_ids).await;
+        let created: HashSet<&str> = object_changes
+            .iter()
+            .filter_map(|change| match change {
+                RawObjectChange::Created { object_id, .. } => Some(object_id.as_str()),
+                _ => None,
+            })
+            .collect();
+        for child in children {
+            let parent = match changed_parents.get(child.parent_id.as_str()) {
+                Some(&(version, ..)) => lookup_version(&store, &child.parent_id, version)
+                    .ok()
+                    .and_then(|parent| parent.details.as_ref()),
+                None => latest
+                    .get(&child.parent_id)
+                    .and_then(|parent| parent.as_ref().ok()),
+            };
+            match parent {
+                Some(parent) => {
+                    if let Some(field_name) = object_field_name(parent, container_id) {
+                        // A value added to the container in this transaction
+                        // brings in its whole balance.
+                        let previous_version = if created.contains(child.parent_id.as_str()) {
+                            None
+                        } else {
+                            child.previous_version
+                        };
+                        candidates.push(FieldCandidate {
+                            object_id: child.object_id,
+                            version: Some(child.version),
+                            previous_version,
+                            field_name: Some(field_name),
+                        });
+                    }
+                }
+                None => report.failures.push(ObjectFailure {
+                    object_id: child.object_id,
+                    reason: ObjectFailureReason::UnresolvedOwner {
+                        owner_id: child.parent_id,
+                    },
+                }),
+            }
+        }
+
+        // Removed fields: plain fields report their own previous balance,
+        // dynamic object fields the previous balance of the value they held.
+        self.load_versions(removed_fields.clone(), &mut store).await;
+        for (object_id, previous_version) in removed_fields {
+            let field = match lookup_version(&store, &object_id, &previous_version) {
+                Ok(field) => field,
+                Err(reason) => {
+                    report.failures.push(ObjectFailure { object_id, reason });
+                    continue;
+                }
+            };
+            let Some(details) = field.details.as_ref() else {
+                report.failures.push(ObjectFailure {
+                    object_id,
+                    reason: ObjectFailureReason::MissingDetails {
+                        version: previous_version,
+                    },
+                });
+                continue;
+            };
+            if !is_owned_by(details, container_id) {
+                continue;
+            }
+
+            match object_field_value(details) {
+                Some((value_id, field_name)) => match modified_at_versions.get(value_id.as_str()) {
+                    Some(value_version) => candidates.push(FieldCandidate {
+                        object_id: value_id,
+                        version: None,
+                        previous_version: Some(value_version.to_string()),
+                        field_name: Some(field_name),
+                    }),
+                    None => report.failures.push(ObjectFailure {
+                        object_id: value_id,
+                        reason: ObjectFailureReason::MissingInputVersion,
+                    }),
+                },
+                None => candidates.push(FieldCandidate {
+                    object_id,
+                    version: None,
+                    previous_version: Some(previous_version),
+                    field_name: None,
+                }),
+            }
+        }
+
+        let requests = candidates
+            .iter()
+            .flat_map(|candidate| {
+                candidate
+                    .version
+                    .iter()
+                    .chain(&candidate.previous_version)
+                    .map(|version| (candidate.object_id.clone(), version.clone()))
+            })
+            .filter(|key| !store.contains_key(key))
+            .collect();
+        self.load_versions(requests, &mut store).await;
+
+        for candidate in candidates {
+            let current_obj = candidate
+                .version
+                .as_ref()
+                .map(|version| lookup_version(&store, &candidate.object_id, version))
+                .transpose();
+            let previous_obj = candidate
+                .previous_version
+                .as_ref()
+                .map(|version| lookup_version(&store, &candidate.object_id, version))
+                .transpose();
+            let outcome = match (current_obj, previous_obj) {
+                (Ok(current_obj), Ok(previous_obj)) => {
+                    self.process_object(current_obj, previous_obj).await
+                }
+                (Err(reason), _) | (_, Err(reason)) => Err(reason),
+            };
+
+            match outcome {
+                Ok(Some(mut change)) => {
+                    change.object_id = candidate.object_id;
+                    if candidate.field_name.is_some() {
+                        change.field_name = candidate.field_name;
+                    }
+                    report.changes.push(change);
+                }
+                Ok(None) => {}
+                Err(reason) => report.failures.push(ObjectFailure {
+                    object_id: candidate.object_id,
+                    reason,
+                }),
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Changes of every `Balance<T>` and `Coin<T>` held in the fields of
+    /// `object_id`, including fields of nested structs, during the
+    /// transaction. Unchanged balances are omitted; an object the transaction
+    /// did not change fails with [`ClientError::ObjectNotInTransaction`], and
+    /// one whose versions or coin decimals cannot be loaded fails with
+    /// [`ClientError::IncompleteBalanceData`] listing every such problem.
+    pub async fn get_object_balance_changes(
+        &self,
+        tx_digest: &str,
+        object_id: &str,
+    ) -> Result<Vec<ObjectBalanceChange>, ClientError> {
+        let result: ObjectChangesResponse = self
+            .call(
+                "sui_getTransactionBlock",
+                json!([tx_digest, { "showObjectChanges": true, "showEffects": true }]),
+            )
+            .await?;
+        let version = result
+            .object_changes
+            .iter()
+            .flatten()
+            .find_map(|change| match change {
+                RawObjectChange::Created {
+                    object_id: id,
+                    version,
+                    ..
+                }
+                | RawObjectChange::Mutated {
+                    object_id: id,
+                    version,
+                    ..
+                }
+                | RawObjectChange::Transferred {
+                    object_id: id,
+                    version,
+                    ..
+                } if same_address(id, object_id) => Some(version.clone()),
+                _ => None,
+            });
+        let previous_version = result
+            .effects
+            .iter()
+            .flat_map(|effects| &effects.modified_at_versions)
+            .find(|modified| same_address(&modified.object_id, object_id))
+            .map(|modified| modified.sequence_number.clone());
+        if version.is_none() && previous_version.is_none() {
+            return Err(ClientError::ObjectNotInTransaction {
+                tx_digest: tx_digest.to_string(),
+                object_id: object_id.to_string(),
+            });
+        }
+
+        let mut store = VersionStore::new();
+        let requests = version
+            .iter()
+            .chain(&previous_version)
+            .map(|version| (object_id.to_string(), version.clone()))
+            .collect();
+        self.load_versions(requests, &mut store).await;
+
+        let failed = |reason| {
+            ClientError::IncompleteBalanceData(vec![ObjectFailure {
+                object_id: object_id.to_string(),
+                reason,
+            }])
+        };
+        let mut layouts = HashMap::new();
+        let mut balances = [BTreeMap::new(), BTreeMap::new()];
+        for (slot, version) in balances.iter_mut().zip([&version, &previous_version]) {
+            if let Some(version) = version {
+                let object = lookup_version(&store, object_id, version).map_err(failed)?;
+                *slot = self
+                    .collect_balances(object, &mut layouts)
+                    .await
+                    .map_err(failed)?;
+            }
+        }
+        let [current, previous] = balances;
+
+        let paths: BTreeSet<&String> = current.keys().chain(previous.keys()).collect();
+        let mut changes = Vec::new();
+        let mut failures = Vec::new();
+        for path in paths {
+            let (current_entry, previous_entry) = (current.get(path), previous.get(path));
+            let current_value =
+                current_entry.map_or_else(BigUint::default, |(_, value)| value.clone());
+            let previous_value =
+                previous_entry.map_or_else(BigUint::default, |(_, value)| value.clone());
+            if current_value == previous_value {
+                continue;
+            }
+            let value_diff =
+                BigInt::from(current_value.clone()) - BigInt::from(previous_value.clone());
+
+            let coin_type = current_entry
+                .or(previous_entry)
+                .map(|(coin_type, _)| coin_type.clone())
+                .unwrap_or_default();
+            let decimals = match self.get_coin_decimals(&coin_type).await {
+                Ok(decimals) => decimals,
+                Err(error) => {
+                    let reason = decimals_failure(error);
+                    if !failures
+                        .iter()
+                        .any(|failure: &ObjectFailure| failure.reason == reason)
+                    {
+                        failures.push(ObjectFailure {
+                            object_id: object_id.to_string(),
+                            reason,
+                        });
+                    }
+                    continue;
+                }
+            };
+            changes.push(ObjectBalanceChange {
+                object_id: object_id.to_string(),
+                field_path: path.clone(),
+                coin_type,
+                previous_value,
+                current_value,
+                value_diff,
+                decimals,
+            });
+        }
+
+        if failures.is_empty() {
+            Ok(changes)
+        } else {
+            Err(ClientError::IncompleteBalanceData(failures))
+        }
+    }
+
+    /// Walks the fields of a Move object using the normalized struct layouts
+    /// and collects every `Balance<T>` and `Coin<T>`, keyed by field path.
+    /// The JSON rendering alone cannot tell a `Balance<T>` from a `u64`.
+    /// Options are followed to their value and vector elements are keyed by
+    /// index, e.g. `rewards[1]`.
+    async fn collect_balances(
+        &self,
+        object: &PastObjectResponse,
+        layouts: &mut HashMap<String, Vec<RawNormalizedField>>,
+    ) -> Result<BTreeMap<String, (String, BigUint)>, ObjectFailureReason> {
+        let mut balances = BTreeMap::new();
+        let Some(content) = object.details.as_ref().and_then(|d| d.content.as_ref()) else {
+            return Ok(balances);
+        };
+        let (Some(object_type), Some(fields)) = (content.type_.as_deref(), content.fields.as_ref())
+        else {
+            return Ok(balances);
+        };
+        let Ok(tag @ TypeTag::Struct(_)) = object_type.parse::<TypeTag>() else {
+            return Ok(balances);
+        };
+
+        let root = json!({ "fields": fields });
+        let mut pending = vec![(tag, &root, String::new())];
+        while let Some((tag, value, path)) = pending.pop() {
+            let unparsable = || ObjectFailureReason::UnparsableBalance {
+                object_type: tag.to_string(),
+            };
+            match &tag {
+                TypeTag::Vector(inner) if may_hold_balance(inner) => {
+                    let items = value.as_array().ok_or_else(unparsable)?;
+                    for (index, item) in items.iter().enumerate() {
+                        pending.push(((**inner).clone(), item, format!("{path}[{index}]")));
+                    }
+                }
+                TypeTag::Struct(struct_tag) => {
+                    if let Some(coin_type) = struct_tag.coin_type() {
+                        // A `Balance<T>` renders as its value, a `Coin<T>` as a
+                        // struct with a `balance` field.
+                        let amount = if struct_tag.is("0x2", "balance", "Balance") {
+                            Some(value)
+                        } else {
+                            value.pointer("/fields/balance")
+                        };
+                        let amount = amount
+                            .map(parse_amount)
+                            .transpose()
+                            .map_err(|value| ObjectFailureReason::InvalidAmount {
+                                object_type: tag.to_string(),
+                                value,
+                            })?
+                            .flatten()
+                            .ok_or_else(unparsable)?;
+                        // Keep the node's rendering when the coin type is
+                        // one of the object's type arguments.
+                        let coin_type = find_rendering(object_type, coin_type)
+                            .map_or_else(|| coin_type.to_string(), str::to_string);
+                        balances.insert(path, (coin_type, amount));
+                    } else if struct_tag.is("0x1", "option", "Option") {
+                        let Some(inner) = struct_tag
+                            .type_params
+                            .first()
+                            .filter(|inner| may_hold_balance(inner))
+                        else {
+                            continue;
+                        };
+                        // Rendered as the value itself or `null`, or as a struct
+                        // holding a vector of at most one element.
+                        match value.pointer("/fields/vec") {
+                            Some(Value::Array(items)) => {
+                                for item in items {
+                                    pending.push((inner.clone(), item, path.clone()));
+                                }
+                            }
+                            Some(_) => return Err(unparsable()),
+                            None if value.is_null() => {}
+                            None => pending.push((inner.clone(), value, path)),
+                        }
+                    } else if let Some(fields) = value.get("fields") {
+                        let key = format!(
+                            "{}::{}::{}",
+                            struct_tag.address, struct_tag.module, struct_tag.name
+                        );
+                        if !layouts.contains_key(&key) {
+                            let layout: RawNormalizedStruct = self
+                                .call(
+                                    "sui_getNormalizedMoveStruct",
+                                    json!([
+                                        struct_tag.address.to_string(),
+                                        struct_tag.module,
+                                        struct_tag.name
+                                    ]),
+                                )
+                                .await
+                                .map_err(|e| ObjectFailureReason::Rpc {
+                                    message: e.to_string(),
+                                })?;
+                            layouts.insert(key.clone(), layout.fields);
+                        }
+
+                        for field in &layouts[&key] {
+                            let Some(value) = fields.get(&field.name) else {
+                                continue;
+                            };
+                            let Some(field_type) =
+                                TypeTag::from_normalized(&field.type_, &struct_tag.type_params)
+                            else {
+                                continue;
+                            };
+                            let field_path = if path.is_empty() {
+                                field.name.clone()
+                            } else {
+                                format!("{path}.{}", field.name)
+                            };
+                            pending.push((field_type, value, field_path));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(balances)
+    }
+
+    /// Computes the balance change of an object from its versions after and
+    /// before the transaction. A missing current version means the object
+    /// was deleted or wrapped, so its whole previous balance flowed out.
+    async fn process_object(
+        &self,
+        current_obj: Option<&PastObjectResponse>,
+        previous_obj: Option<&PastObjectResponse>,
+    ) -> Result<Option<DynamicFieldBalanceChange>, ObjectFailureReason> {
+        let Some(typed_obj) = current_obj.or(previous_obj) else {
+            return Ok(None);
+        };
+        let object_type = self.extract_object_type(typed_obj).unwrap_or_default();
+        let unparsable = || ObjectFailureReason::UnparsableBalance {
+            object_type: object_type.clone(),
+        };
+        let invalid = |value| ObjectFailureReason::InvalidAmount {
+            object_type: object_type.clone(),
+            value,
+        };
+
+        // Only `Balance<T>` and `Coin<T>` values are balances; a plain integer
+        // such as a `Table<K, u64>` counter is skipped.
+        if !holds_coin(&object_type) {
+            return Ok(None);
+        }
+        let Some(typed_val) = self.extract_balance_value(typed_obj).map_err(invalid)? else {
+            return Err(unparsable());
+        };
+        let (curr_val, prev_val) = match (current_obj, previous_obj) {
+            (Some(_), Some(obj)) => (
+                typed_val,
+                self.extract_balance_value(obj)
+                    .map_err(invalid)?
+                    .ok_or_else(unparsable)?,
+            ),
+            (Some(_), None) => (typed_val, BigUint::default()),
+            (None, _) => (BigUint::default(), typed_val),
+        };
+        let diff = BigInt::from(curr_val.clone()) - BigInt::from(prev_val.clone());
+
+        // Extract coin type
+        let coin_type = self.extract_coin_type(&object_type);
+
+        // Get decimals
+        let decimals = self
+            .get_coin_decimals(&coin_type)
+            .await
+            .map_err(decimals_failure)?;
+
+        Ok(Some(DynamicFieldBalanceChange {
+            object_id: String::new(),
+            field_name: field_name(typed_obj),
+            coin_type,
+            previous_value: prev_val,
+            current_value: curr_val,
+            value_diff: diff,
+            decimals,
+        }))
+    }
+
+    /// Loads object versions with `sui_tryMultiGetPastObjects`, recording a
+    /// failed call against every version it covered. Versions that are not
+    /// decimal u64 strings are recorded as invalid without being requested.
+    async fn load_versions(&self, requests: Vec<(String, String)>, store: &mut VersionStore) {
+        let (requests, invalid): (Vec<_>, Vec<_>) = requests
+            .into_iter()
+            .partition(|(_, version)| version.parse::<u64>().is_ok());
+        for (object_id, version) in invalid {
+            let reason = ObjectFailureReason::InvalidVersion {
+                version: version.clone(),
+            };
+            store.insert((object_id, version), Err(reason));
+        }
+
+        for chunk in requests.chunks(MAX_MULTI_GET_OBJECTS) {
+            let params: Vec<Value> = chunk
+                .iter()
+                .map(|(object_id, version)| json!({ "objectId": object_id, "version": version }))
+                .collect();
+
+            let response: Result<Vec<PastObjectResponse>, ClientError> = self
+                .call(
+                    "sui_tryMultiGetPastObjects",
+                    json!([params, { "showOwner": true, "showContent": true }]),
+                )
+                .await;
+
+            match response {
+                Ok(objects) => {
+                    let mut objects = objects.into_iter();
+                    for key in chunk {
+                        let object = objects.next().ok_or_else(|| ObjectFailureReason::Rpc {
+                            message: ClientError::MissingResult.to_string(),
+                        });
+                        store.insert(key.clone(), object);
+                    }
+                }
+                Err(e) => {
+                    for key in chunk {
+                        let reason = ObjectFailureReason::Rpc {
+                            message: e.to_string(),
+                        };
+                        store.insert(key.clone(), Err(reason));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Loads the latest version of each object with `sui_multiGetObjects`,
+    /// for objects whose version at the transaction is unknown.
+    async fn get_latest_objects(
+        &self,
+        object_ids: &[String],
+    ) -> HashMap<String, Result<ObjectDetails, String>> {
+        let mut objects = HashMap::new();
+        for chunk in object_ids.chunks(MAX_MULTI_GET_OBJECTS) {
+            let response: Result<Vec<RawObjectResponse>, ClientError> = self
+                .call(
+                    "sui_multiGetObjects",
+                    json!([chunk, { "showOwner": true, "showContent": true }]),
+                )
+                .await;
+
+            match response {
+                Ok(responses) => {
+                    let mut responses = responses.into_iter();
+                    for object_id in chunk {
+                        let object = responses
+                            .next()
+                            .and_then(|response| response.data)
+                            .ok_or_else(|| format!("object {object_id} not found"));
+                        objects.insert(object_id.clone(), object);
+                    }
+                }
+                Err(e) => {
+                    for object_id in chunk {
+                        objects.insert(object_id.clone(), Err(e.to_string()));
+                    }
+                }
+            }
+        }
+        objects
+    }
+
+    /// Reads the amount held by a `Coin`, `Balance` or numeric dynamic field.
+    /// Fails with the offending rendering when an amount cannot be
+    /// represented exactly.
+    fn extract_balance_value(&self, obj: &PastObjectResponse) -> Result<Option<BigUint>, String> {
+        if obj.status != "VersionFound" {
+            return Ok(None);
+        }
+
+        let Some(fields) = obj
+            .details
+            .as_ref()
+            .and_then(|details| details.content.as_ref())
+            .filter(|content| content.data_type == "moveObject")
+            .and_then(|content| content.fields.as_ref())
+        else {
+            return Ok(None);
+        };
+
+        // Direct balance field, then a value field that may itself be a
+        // nested object such as a `Coin<T>`.
+        let candidates = [
+            fields.get("balance"),
+            fields.pointer("/balance/value"),
+            fields.get("value"),
+            fields.pointer("/value/fields/balance"),
+            fields.pointer("/value/fields/value"),
+        ];
+        for value in candidates.into_iter().flatten() {
+            if let Some(amount) = parse_amount(value)? {
+                return Ok(Some(amount));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn extract_object_type(&self, obj: &PastObjectResponse) -> Option<String> {
+        if obj.status != "VersionFound" {
+            return None;
+        }
+
+        obj.details.as_ref()?.content.as_ref()?.type_.clone()
+    }
+
+    fn extract_coin_type(&self, type_str: &str) -> String {
+        // Extract coin type from patterns like:
+        // "0x2::coin::Coin<0x2::sui::SUI>"
+        // "0x2::dynamic_field::Field<0x1::type_name::TypeName, 0x2::coin::Coin<0x2::sui::SUI>>"
+        // keeping the node's rendering so it matches `BalanceChange::coin_type`.
+        type_str
+            .parse::<TypeTag>()
+            .ok()
+            .and_then(|tag| find_rendering(type_str, tag.find_coin_type()?))
+            .unwrap_or(type_str)
+            .to_string()
+    }
+}
+
+/// Reason recorded for an object whose coin decimals could not be loaded.
+fn decimals_failure(error: ClientError) -> ObjectFailureReason {
+    match error {
+        ClientError::UnknownDecimals { coin_type, reason } => {
+            ObjectFailureReason::UnknownDecimals { coin_type, reason }
+        }
+        error => ObjectFailureReason::Rpc {
+            message: error.to_string(),
+        },
+    }
+}
+
+/// Reads an amount rendered either as a JSON number or a decimal string of
+/// any length. Other strings are not amounts; numbers that are not
+/// non-negative integers are rejected rather than rounded.
+fn parse_amount(value: &Value) -> Result<Option<BigUint>, String> {
+    match value {
+        Value::Number(number) => number
+            .as_u64()
+            .map(|amount| Some(BigUint::from(amount)))
+            .ok_or_else(|| number.to_string()),
+        Value::String(digits)
+            if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) =>
+        {
+            Ok(digits.parse().ok())
+        }
+        _ => Ok(None),
+    }
+}
+
+fn holds_coin(object_type: &str) -> bool {
+    object_type
+        .parse::<TypeTag>()
+        .is_ok_and(|tag| tag.find_coin_type().is_some())
+}
+
+/// Whether a value of type `tag` may contain a `Balance` or `Coin`: any
+/// struct, or a vector of structs.
+fn may_hold_balance(tag: &TypeTag) -> bool {
+    match tag {
+        TypeTag::Struct(_) => true,
+        TypeTag::Vector(inner) => may_hold_balance(inner),
+        _ => false,
+    }
+}
+
+fn is_dynamic_field(object_type: &str) -> bool {
+    object_type.parse::<TypeTag>().is_ok_and(|tag| {
+        tag.as_struct()
+            .is_some_and(|tag| tag.is("0x2", "dynamic_field", "Field"))
+    })
+}
+
+/// Whether `object_type` is the `Field<Wrapper<K>, ID>` that links a dynamic
+/// object field to its value.
+fn is_object_field_wrapper(object_type: &str) -> bool {
+    object_type.parse::<TypeTag>().is_ok_and(|tag| {
+        tag.as_struct()
+            .filter(|tag| tag.is("0x2", "dynamic_field", "Field"))
+            .and_then(|field| field.type_params.first()?.as_struct())
+            .is_some_and(|wrapper| wrapper.is("0x2", "dynamic_object_field", "Wrapper"))
+    })
+}
+
+fn is_owned_by(details: &ObjectDetails, owner_id: &str) -> bool {
+    matches!(
+        &details.owner,
+        Some(ObjectOwner::ObjectOwner { object_owner }) if same_address(object_owner, owner_id)
+    )
+}
+
+/// Key stored in a `dynamic_field::Field`.
+fn field_name(obj: &PastObjectResponse) -> Option<Value> {
+    obj.details
+        .as_ref()?
+        .content
+        .as_ref()
+        .filter(|content| content.type_.as_deref().is_some_and(is_dynamic_field))?
+        .fields
+        .as_ref()?
+        .get("name")
+        .cloned()
+}
+
+/// If `details` is the `Field<Wrapper<K>, ID>` of a dynamic object field
+/// owned by `container_id`, returns the key it stores.
+fn object_field_name(details: &ObjectDetails, container_id: &str) -> Option<Value> {
+    if !is_owned_by(details, container_id) {
+        return None;
+    }
+    object_field_value(details).map(|(_, name)| name)
+}
+
+/// Splits the `Field<Wrapper<K>, ID>` of a dynamic object field into the ID
+/// of the value object and the key `K`.
+fn object_field_value(details: &ObjectDetails) -> Option<(String, Value)> {
+    let content = details.content.as_ref()?;
+    if !is_object_field_wrapper(content.type_.as_deref()?) {
+        return None;
+    }
+
+    let fields = content.fields.as_ref()?;
+    let value_id = fields.get("value")?.as_str()?.to_string();
+    let name = fields.get("name")?;
+    let name = name
+        .pointer("/fields/name")
+        .cloned()
+        .unwrap_or_else(|| name.clone());
+    Some((value_id, name))
+}
+
+/// Looks up a loaded version, checking that the node actually returned it.
+fn lookup_version<'a>(
+    store: &'a VersionStore,
+    object_id: &str,
+    version: &str,
+) -> Result<&'a PastObjectResponse, ObjectFailureReason> {
+    match store.get(&(object_id.to_string(), version.to_string())) {
+        Some(Ok(object)) if object.status == "VersionFound" => Ok(object),
+        Some(Err(reason)) => Err(reason.clone()),
+        other => Err(ObjectFailureReason::VersionUnavailable {
+            version: version.to_string(),
+            status: match other {
+                Some(Ok(object)) => object.status.clone(),
+                _ => "Missing".to_string(),
+            },
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::Method::POST;
+    use httpmock::MockServer;
+    use std::net::TcpListener;
+
+    fn ensure_socket_permission() -> bool {
+        TcpListener::bind("127.0.0.1:0").is_ok()
+    }
+
+    const BAG_FIELD_TYPE: &str =
+        "0x2::dynamic_field::Field<0x1::type_name::TypeName, 0x2::balance::Balance<0x2::sui::SUI>>";
+
+    fn bag_field(value: Value) -> Value {
+        json!({
+            "status": "VersionFound",
+            "details": {
+                "owner": { "ObjectOwner": "0xba9" },
+                "content": {
+                    "dataType": "moveObject",
+                    "type": BAG_FIELD_TYPE,
+                    "fields": { "name": "0x2::sui::SUI", "value": value },
+                }
+            }
+        })
+    }
+
+    /// Mocks a transaction that mutates bag field 0xf1e1 (version 7 -> 8),
+    /// creates bag field 0xf1e2 and touches an unrelated object, answering the
+    /// bulk version lookup with `past_objects`.
+    async fn mock_bag_transaction(
+        server: &MockServer,
+        past_objects: Vec<Value>,
+    ) -> httpmock::Mock<'_> {
+        server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .json_body_partial(r#"{"method": "sui_getTransactionBlock"}"#);
+                then.status(200).json_body(json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": {
+                        "digest": "BAGTX",
+                        "objectChanges": [
+                            {
+                                "type": "mutated",
+                                "objectId": "0xf1e1",
+                                "objectType": BAG_FIELD_TYPE,
+                                "version": "8",
+                                "previousVersion": "7",
+                                "owner": { "ObjectOwner": "0xba9" },
+                            },
+                            {
+                                "type": "created",
+                                "objectId": "0xf1e2",
+                                "objectType": BAG_FIELD_TYPE,
+                                "version": "8",
+                                "owner": { "ObjectOwner": "0xba9" },
+                            },
+                            {
+                                "type": "mutated",
+                                "objectId": "0xbeef",
+                                "version": "8",
+                                "previousVersion": "3",
+                                "owner": { "AddressOwner": "0x5905" },
+                            },
+                        ],
+                    },
+                }));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .json_body_partial(r#"{"method": "suix_getCoinMetadata"}"#);
+                then.status(200)
+                    .json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": { "decimals": 9 } }));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST).json_body_partial(
+                    r#"{
+                "method": "sui_tryMultiGetPastObjects",
+                "params": [[
+                    { "objectId": "0xf1e1", "version": "8" },
+                    { "objectId": "0xf1e1", "version": "7" },
+                    { "objectId": "0xf1e2", "version": "8" }
+                ]]
+            }"#,
+                );
+                then.status(200)
+                    .json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": past_objects }));
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn fetches_bag_object_versions_in_bulk() {
+        if !ensure_socket_permission() {
+            eprintln!("skipping fetches_bag_object_versions_in_bulk: binding to localhost is not permitted in this environment");
+            return;
+        }
+
+        let server = MockServer::start_async().await;
+        let past_objects = mock_bag_transaction(
+            &server,
+            vec![
+                bag_field(json!("150")),
+                bag_field(json!("100")),
+                bag_field(json!("40")),
+            ],
+        )
+        .await;
+
+        let client = TxParseClient::new(server.base_url());
+        let changes = client
+            .get_bag_dynamic_field_balance_changes("BAGTX", "0xba9")
+            .await
+            .expect("bag changes should load");
+
+        past_objects.assert_async().await;
+        let diffs: Vec<BigInt> = changes
+            .iter()
+            .map(|change| change.value_diff.clone())
+            .collect();
+        assert_eq!(diffs, vec![BigInt::from(50), BigInt::from(40)]);
+        assert!(changes.iter().all(|change| change.decimals == 9));
+        assert_eq!(changes[0].object_id, "0xf1e1");
+        assert_eq!(changes[0].field_name, Some(json!("0x2::sui::SUI")));
+    }
+
+    #[tokio::test]
+    async fn reports_objects_that_could_not_be_processed() {
+        if !ensure_socket_permission() {
+            eprintln!("skipping reports_objects_that_could_not_be_processed: binding to localhost is not permitted in this environment");
+            return;
+        }
+
+        let server = MockServer::start_async().await;
+        mock_bag_transaction(
+            &server,
+            vec![
+                bag_field(json!("150")),
+                json!({ "status": "VersionNotFound", "details": ["0xf1e1", 7] }),
+                bag_field(json!({ "unexpected": true })),
+            ],
+        )
+        .await;
+
+        let client = TxParseClient::new(server.base_url());
+        let report = client
+            .get_bag_dynamic_field_balance_report("BAGTX", "0xba9")
+            .await
+            .expect("report should load");
+
+        assert!(report.changes.is_empty());
+        assert_eq!(
+            report.failures,
+            vec![
+                ObjectFailure {
+                    object_id: "0xf1e1".to_string(),
+                    reason: ObjectFailureReason::VersionUnavailable {
+                        version: "7".to_string(),
+                        status: "VersionNotFound".to_string(),
+                    },
+                },
+                ObjectFailure {
+                    object_id: "0xf1e2".to_string(),
+                    reason: ObjectFailureReason::UnparsableBalance {
+                        object_type: BAG_FIELD_TYPE.to_string(),
+                    },
+                },
+            ]
+        );
+
+        let changes = client
+            .get_bag_dynamic_field_balance_changes("BAGTX", "0xba9")
+            .await
+            .expect("lenient mode should skip failed objects");
+        assert!(changes.is_empty());
+
+        let err = client
+            .get_bag_dynamic_field_balance_changes_strict("BAGTX", "0xba9")
+            .await
+            .expect_err("strict mode should fail");
+        assert!(matches!(err, ClientError::IncompleteBalanceData(failures) if failures.len() == 2));
+    }
+
+    #[tokio::test]
+    async fn skips_plain_integer_fields() {
+        if !ensure_socket_permission() {
+            eprintln!("skipping skips_plain_integer_fields: binding to localhost is not permitted in this environment");
+            return;
+        }
+
+        const COUNTER_TYPE: &str = "0x2::dynamic_field::Field<address, u64>";
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .json_body_partial(r#"{"method": "sui_getTransactionBlock"}"#);
+                then.status(200).json_body(json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": {
+                        "digest": "BAGTX",
+                        "objectChanges": [
+                            {
+                                "type": "created",
+                                "objectId": "0xf1e2",
+                                "objectType": BAG_FIELD_TYPE,
+                                "version": "8",
+                                "owner": { "ObjectOwner": "0xba9" },
+                            },
+                            {
+                                "type": "created",
+                                "objectId": "0xf1e3",
+                                "objectType": COUNTER_TYPE,
+                                "version": "8",
+                                "owner": { "ObjectOwner": "0xba9" },
+                            },
+                        ],
+                    },
+                }));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST).json_body_partial(
+                    r#"{
+                "method": "sui_tryMultiGetPastObjects",
+                "params": [[
+                    { "objectId": "0xf1e2", "version": "8" },
+                    { "objectId": "0xf1e3", "version": "8" }
+                ]]
+            }"#,
+                );
+                then.status(200).json_body(json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": [
+                        bag_field(json!("150")),
+                        {
+                            "status": "VersionFound",
+                            "details": {
+                                "owner": { "ObjectOwner": "0xba9" },
+                                "content": {
+                                    "dataType": "moveObject",
+                                    "type": COUNTER_TYPE,
+                                    "fields": { "name": "0x5905", "value": "12" },
+                                }
+                            }
+                        },
+                    ],
+                }));
+            })
+            .await;
+        let metadata = server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .json_body_partial(r#"{"method": "suix_getCoinMetadata"}"#);
+                then.status(200)
+                    .json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": { "decimals": 9 } }));
+            })
+            .await;
+
+        let client = TxParseClient::new(server.base_url());
+        let changes = client
+            .get_bag_dynamic_field_balance_changes_strict("BAGTX", "0xba9")
+            .await
+            .expect("the counter should not be treated as a balance");
+
+        metadata.assert_hits_async(1).await;
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].object_id, "0xf1e2");
+        assert_eq!(changes[0].current_value, BigUint::from(150u8));
+    }
+
+    #[tokio::test]
+    async fn keeps_amounts_beyond_u64_and_rejects_inexact_numbers() {
+        if !ensure_socket_permission() {
+            eprintln!("skipping keeps_amounts_beyond_u64_and_rejects_inexact_numbers: binding to localhost is not permitted in this environment");
+            return;
+        }
+
+        let server = MockServer::start_async().await;
+        mock_bag_transaction(
+            &server,
+            vec![
+                bag_field(json!("1000000000000000000000000000000000000000")),
+                bag_field(json!("340282366920938463463374607431768211455")),
+                bag_field(json!(-5)),
+            ],
+        )
+        .await;
+
+        let client = TxParseClient::new(server.base_url());
+        let report = client
+            .get_bag_dynamic_field_balance_report("BAGTX", "0xba9")
+            .await
+            .expect("report should load");
+
+        assert_eq!(report.changes.len(), 1);
+        assert_eq!(
+            report.changes[0].value_diff.to_string(),
+            "659717633079061536536625392568231788545"
+        );
+        assert_eq!(
+            report.failures,
+            vec![ObjectFailure {
+                object_id: "0xf1e2".to_string(),
+                reason: ObjectFailureReason::InvalidAmount {
+                    object_type: BAG_FIELD_TYPE.to_string(),
+                    value: "-5".to_string(),
+                },
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_malformed_versions_without_requesting_them() {
+        if !ensure_socket_permission() {
+            eprintln!("skipping reports_malformed_versions_without_requesting_them: binding to localhost is not permitted in this environment");
+            return;
+        }
+
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .json_body_partial(r#"{"method": "sui_getTransactionBlock"}"#);
+                then.status(200).json_body(json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": {
+                        "digest": "BAGTX",
+                        "objectChanges": [{
+                            "type": "mutated",
+                            "objectId": "0xf1e1",
+                            "objectType": BAG_FIELD_TYPE,
+                            "version": "8",
+                            "previousVersion": "0x7",
+                            "owner": { "ObjectOwner": "0xba9" },
+                        }],
+                    },
+                }));
+            })
+            .await;
+        let past_objects = server
+            .mock_async(|when, then| {
+                when.method(POST).json_body_partial(
+                    r#"{
+                "method": "sui_tryMultiGetPastObjects",
+                "params": [[{ "objectId": "0xf1e1", "version": "8" }]]
+            }"#,
+                );
+                then.status(200).json_body(json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": [bag_field(json!("150"))],
+                }));
+            })
+            .await;
+
+        let client = TxParseClient::new(server.base_url());
+        let report = client
+            .get_bag_dynamic_field_balance_report("BAGTX", "0xba9")
+            .await
+            .expect("report should load");
+
+        past_objects.assert_async().await;
+        assert!(report.changes.is_empty());
+        assert_eq!(
+            report.failures,
+            vec![ObjectFailure {
+                object_id: "0xf1e1".to_string(),
+                reason: ObjectFailureReason::InvalidVersion {
+                    version: "0x7".to_string(),
+                },
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_outflows_of_deleted_and_wrapped_fields() {
+        if !ensure_socket_permission() {
+            eprintln!("skipping reports_outflows_of_deleted_and_wrapped_fields: binding to localhost is not permitted in this environment");
+            return;
+        }
+
+        let server = MockServer::start_async().await;
+        server.mock_async(|when, then| {
+            when.method(POST).json_body_partial(r#"{"method": "sui_getTransactionBlock"}"#);
+            then.status(200).json_body(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": {
+                    "digest": "BAGTX",
+                    "objectChanges": [
+                        { "type": "deleted", "objectId": "0xf1e3", "objectType": BAG_FIELD_TYPE, "version": "9" },
+                        { "type": "wrapped", "objectId": "0xf1e4", "objectType": BAG_FIELD_TYPE, "version": "9" },
+                        { "type": "deleted", "objectId": "0xc0c0", "objectType": "0x2::coin::Coin<0x2::sui::SUI>", "version": "9" },
+                    ],
+                    "effects": {
+                        "modifiedAtVersions": [
+                            { "objectId": "0xf1e3", "sequenceNumber": "5" },
+                            { "objectId": "0xf1e4", "sequenceNumber": "6" },
+                            { "objectId": "0xc0c0", "sequenceNumber": "2" },
+                        ],
+                    },
+                },
+            }));
+        }).await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .json_body_partial(r#"{"method": "suix_getCoinMetadata"}"#);
+                then.status(200)
+                    .json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": { "decimals": 9 } }));
+            })
+            .await;
+        let mut foreign_field = bag_field(json!("30"));
+        foreign_field["details"]["owner"] = json!({ "ObjectOwner": "0xa11" });
+        let past_objects = server
+            .mock_async(|when, then| {
+                when.method(POST).json_body_partial(
+                    r#"{
+                "method": "sui_tryMultiGetPastObjects",
+                "params": [[
+                    { "objectId": "0xf1e3", "version": "5" },
+                    { "objectId": "0xf1e4", "version": "6" }
+                ]]
+            }"#,
+                );
+                then.status(200).json_body(json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": [bag_field(json!("75")), foreign_field],
+                }));
+            })
+            .await;
+
+        let client = TxParseClient::new(server.base_url());
+        let changes = client
+            .get_bag_dynamic_field_balance_changes("BAGTX", "0xba9")
+            .await
+            .expect("bag changes should load");
+
+        past_objects.assert_async().await;
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].previous_value, BigUint::from(75u8));
+        assert_eq!(changes[0].current_value, BigUint::default());
+        assert_eq!(changes[0].value_diff, BigInt::from(-75));
+    }
+
+    #[test]
+    fn extracts_coin_type_from_nested_generics() {
+        let client = TxParseClient::new("http://localhost");
+
+        assert_eq!(
+            client.extract_coin_type("0x2::dynamic_field::Field<0x1::type_name::TypeName, 0x2::coin::Coin<0xabc::lp::LP<0x2::sui::SUI, 0xdef::usdc::USDC>>>"),
+            "0xabc::lp::LP<0x2::sui::SUI, 0xdef::usdc::USDC>"
+        );
+        assert_eq!(
+            client.extract_coin_type("0x2::balance::Balance<0x06864a6f921804860930db6ddbe2e16acdf8504495ea7481637a1c8b9a8fe54b::cetus::CETUS>"),
+            "0x06864a6f921804860930db6ddbe2e16acdf8504495ea7481637a1c8b9a8fe54b::cetus::CETUS"
+        );
+        assert_eq!(
+            client.extract_coin_type("0xabc::pool::Pool"),
+            "0xabc::pool::Pool"
+        );
+    }
+
+    const OBJECT_FIELD_TYPE: &str =
+        "0x2::dynamic_field::Field<0x2::dynamic_object_field::Wrapper<u64>, 0x2::object::ID>";
+
+    fn object_field(value_id: &str, name: &str) -> Value {
+        json!({
+            "owner": { "ObjectOwner": "0xb0b" },
+            "content": {
+                "dataType": "moveObject",
+                "type": OBJECT_FIELD_TYPE,
+                "fields": {
+                    "name": {
+                        "type": "0x2::dynamic_object_field::Wrapper<u64>",
+                        "fields": { "name": name },
+                    },
+                    "value": value_id,
+                },
+            },
+        })
+    }
+
+    fn coin(balance: &str) -> Value {
+        json!({
+            "status": "VersionFound",
+            "details": {
+                "content": {
+                    "dataType": "moveObject",
+                    "type": "0x2::coin::Coin<0x2::sui::SUI>",
+                    "fields": { "balance": balance },
+                }
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn follows_dynamic_object_fields_to_their_values() {
+        if !ensure_socket_permission() {
+            eprintln!("skipping follows_dynamic_object_fields_to_their_values: binding to localhost is not permitted in this environment");
+            return;
+        }
+
+        let coin_type = "0x2::coin::Coin<0x2::sui::SUI>";
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .json_body_partial(r#"{"method": "sui_getTransactionBlock"}"#);
+                then.status(200).json_body(json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": {
+                        "digest": "OBJBAGTX",
+                        "objectChanges": [
+                            { "type": "mutated", "objectId": "0xc01", "objectType": coin_type, "version": "10", "previousVersion": "9", "owner": { "ObjectOwner": "0xe1" } },
+                            { "type": "created", "objectId": "0xe2", "objectType": OBJECT_FIELD_TYPE, "version": "10", "owner": { "ObjectOwner": "0xb0b" } },
+                            { "type": "mutated", "objectId": "0xc02", "objectType": coin_type, "version": "10", "previousVersion": "4", "owner": { "ObjectOwner": "0xe2" } },
+                            { "type": "deleted", "objectId": "0xe3", "objectType": OBJECT_FIELD_TYPE, "version": "10" },
+                            { "type": "mutated", "objectId": "0xc03", "objectType": coin_type, "version": "10", "previousVersion": "3", "owner": { "AddressOwner": "0x5905" } },
+                            { "type": "mutated", "objectId": "0xf1e9", "objectType": BAG_FIELD_TYPE, "version": "10", "previousVersion": "8", "owner": { "ObjectOwner": "0xa11" } },
+                            { "type": "mutated", "objectId": "0xc04", "objectType": coin_type, "version": "10", "previousVersion": "2", "owner": { "ObjectOwner": "0xacc" } },
+                            { "type": "mutated", "objectId": "0xacc", "objectType": "0xabc::account::Account", "version": "10", "previousVersion": "6", "owner": { "AddressOwner": "0x5905" } },
+                        ],
+                        "effects": {
+                            "modifiedAtVersions": [
+                                { "objectId": "0xc01", "sequenceNumber": "9" },
+                                { "objectId": "0xc02", "sequenceNumber": "4" },
+                                { "objectId": "0xe3", "sequenceNumber": "7" },
+                                { "objectId": "0xc03", "sequenceNumber": "3" },
+                            ],
+                        },
+                    },
+                }));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .json_body_partial(r#"{"method": "suix_getCoinMetadata"}"#);
+                then.status(200)
+                    .json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": { "decimals": 9 } }));
+            })
+            .await;
+        let parents = server
+            .mock_async(|when, then| {
+                when.method(POST).json_body_partial(
+                    r#"{"method": "sui_multiGetObjects", "params": [["0xe1"]]}"#,
+                );
+                then.status(200).json_body(json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": [{ "data": object_field("0xc01", "1") }],
+                }));
+            })
+            .await;
+        let created_parent = server
+            .mock_async(|when, then| {
+                when.method(POST).json_body_partial(
+                    r#"{
+                "method": "sui_tryMultiGetPastObjects",
+                "params": [[{ "objectId": "0xe2", "version": "10" }]]
+            }"#,
+                );
+                then.status(200).json_body(json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": [{ "status": "VersionFound", "details": object_field("0xc02", "2") }],
+                }));
+            })
+            .await;
+        let removed = server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .json_body_partial(r#"{"method": "sui_tryMultiGetPastObjects"}"#)
+                    .body_contains("0xe3");
+                then.status(200).json_body(json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": [{ "status": "VersionFound", "details": object_field("0xc03", "3") }],
+                }));
+            })
+            .await;
+        let values = server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .json_body_partial(r#"{"method": "sui_tryMultiGetPastObjects"}"#)
+                    .body_contains("0xc03");
+                then.status(200).json_body(json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": [
+                        coin("150"),
+                        coin("100"),
+                        coin("40"),
+                        coin("25"),
+                    ],
+                }));
+            })
+            .await;
+
+        let client = TxParseClient::new(server.base_url());
+        let changes = client
+            .get_container_balance_changes("OBJBAGTX", "0xb0b")
+            .await
+            .expect("container changes should load");
+
+        parents.assert_async().await;
+        created_parent.assert_async().await;
+        removed.assert_async().await;
+        values.assert_async().await;
+        let summary: Vec<(&str, Option<&Value>, i128)> = changes
+            .iter()
+            .map(|change| {
+                (
+                    change.object_id.as_str(),
+                    change.field_name.as_ref(),
+                    i128::try_from(&change.value_diff).expect("diff should fit"),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("0xc01", Some(&json!("1")), 50),
+                ("0xc02", Some(&json!("2")), 40),
+                ("0xc03", Some(&json!("3")), -25),
+            ]
+        );
+    }
+
+    fn normalized_struct(fields: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": 1, "result": { "fields": fields } })
+    }
+
+    fn pool(reserve_x: &str, reserve_y: &str, fees: &str, locked: Value, rewards: Value) -> Value {
+        json!({
+            "status": "VersionFound",
+            "details": {
+                "owner": "Shared",
+                "content": {
+                    "dataType": "moveObject",
+                    "type": "0xabc::pool::Pool<0x2::sui::SUI, 0xdef::usdc::USDC>",
+                    "fields": {
+                        "id": { "id": "0xa001" },
+                        "reserve_x": reserve_x,
+                        "reserve_y": reserve_y,
+                        "vault": {
+                            "type": "0xabc::pool::Vault<0x2::sui::SUI>",
+                            "fields": {
+                                "fees": {
+                                    "type": "0x2::coin::Coin<0x2::sui::SUI>",
+                                    "fields": { "id": { "id": "0xa002" }, "balance": fees },
+                                },
+                                "count": "3",
+                            },
+                        },
+                        "lp_supply": "1000",
+                        "locked": {
+                            "type": "0x1::option::Option<0x2::balance::Balance<0x2::sui::SUI>>",
+                            "fields": { "vec": locked },
+                        },
+                        "rewards": rewards,
+                        "bonus": null,
+                    },
+                }
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn reports_removed_fields_it_cannot_trace() {
+        if !ensure_socket_permission() {
+            eprintln!("skipping reports_removed_fields_it_cannot_trace: binding to localhost is not permitted in this environment");
+            return;
+        }
+
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .json_body_partial(r#"{"method": "sui_getTransactionBlock"}"#);
+                then.status(200).json_body(json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": {
+                        "digest": "OBJBAGTX",
+                        "objectChanges": [
+                            { "type": "deleted", "objectId": "0xf1e5", "objectType": BAG_FIELD_TYPE, "version": "9" },
+                            { "type": "deleted", "objectId": "0xf1e6", "objectType": BAG_FIELD_TYPE, "version": "9" },
+                            { "type": "deleted", "objectId": "0xe3", "objectType": OBJECT_FIELD_TYPE, "version": "9" },
+                        ],
+                        "effects": {
+                            "modifiedAtVersions": [
+                                { "objectId": "0xf1e6", "sequenceNumber": "5" },
+                                { "objectId": "0xe3", "sequenceNumber": "7" },
+                            ],
+                        },
+                    },
+                }));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .json_body_partial(r#"{"method": "sui_tryMultiGetPastObjects"}"#);
+                then.status(200).json_body(json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": [
+                        { "status": "VersionFound", "details": 42 },
+                        { "status": "VersionFound", "details": object_field("0xc03", "3") },
+                    ],
+                }));
+            })
+            .await;
+
+        let client = TxParseClient::new(server.base_url());
+        let report = client
+            .get_container_balance_report("OBJBAGTX", "0xb0b")
+            .await
+            .expect("report should load");
+
+        assert!(report.changes.is_empty());
+        assert_eq!(
+            report.failures,
+            vec![
+                ObjectFailure {
+                    object_id: "0xf1e5".to_string(),
+                    reason: ObjectFailureReason::MissingInputVersion,
+                },
+                ObjectFailure {
+                    object_id: "0xf1e6".to_string(),
+                    reason: ObjectFailureReason::MissingDetails {
+                        version: "5".to_string(),
+                    },
+                },
+                ObjectFailure {
+                    object_id: "0xc03".to_string(),
+                    reason: ObjectFailureReason::MissingInputVersion,
+                },
+            ]
+        );
+    }
+
+    /// Mocks a transaction that mutates pool 0xa001 (version 11 -> 12) with
+    /// the layouts of `Pool` and `Vault`, returning the layout mocks. Coin
+    /// metadata is left to the caller.
+    async fn mock_pool_transaction(
+        server: &MockServer,
+    ) -> (httpmock::Mock<'_>, httpmock::Mock<'_>) {
+        server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .json_body_partial(r#"{"method": "sui_getTransactionBlock"}"#);
+                then.status(200).json_body(json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": {
+                        "digest": "SWAPTX",
+                        "objectChanges": [{
+                            "type": "mutated",
+                            "objectId": "0xa001",
+                            "objectType": "0xabc::pool::Pool<0x2::sui::SUI, 0xdef::usdc::USDC>",
+                            "version": "12",
+                            "previousVersion": "11",
+                            "owner": "Shared",
+                        }],
+                        "effects": {
+                            "modifiedAtVersions": [
+                                { "objectId": "0xa001", "sequenceNumber": "11" },
+                            ],
+                        },
+                    },
+                }));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST).json_body_partial(
+                    r#"{
+                "method": "sui_tryMultiGetPastObjects",
+                "params": [[
+                    { "objectId": "0xa001", "version": "12" },
+                    { "objectId": "0xa001", "version": "11" }
+                ]]
+            }"#,
+                );
+                then.status(200).json_body(json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": [
+                        pool("1100", "500", "16", json!(["7"]), json!(["1", "3"])),
+                        pool("1000", "500", "10", json!([]), json!(["1", "2"])),
+                    ],
+                }));
+            })
+            .await;
+
+        let balance = |param: u64| {
+            json!({ "Struct": {
+                "address": "0x2",
+                "module": "balance",
+                "name": "Balance",
+                "typeArguments": [{ "TypeParameter": param }],
+            }})
+        };
+        let pool_layout = server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .json_body_partial(r#"{"method": "sui_getNormalizedMoveStruct"}"#)
+                    .body_contains(r#""Pool""#);
+                then.status(200).json_body(normalized_struct(json!([
+                    { "name": "id", "type": { "Struct": {
+                        "address": "0x2", "module": "object", "name": "UID", "typeArguments": [],
+                    }}},
+                    { "name": "reserve_x", "type": balance(0) },
+                    { "name": "reserve_y", "type": balance(1) },
+                    { "name": "vault", "type": { "Struct": {
+                        "address": "0xabc",
+                        "module": "pool",
+                        "name": "Vault",
+                        "typeArguments": [{ "TypeParameter": 0 }],
+                    }}},
+                    { "name": "lp_supply", "type": "U64" },
+                    { "name": "locked", "type": { "Struct": {
+                        "address": "0x1",
+                        "module": "option",
+                        "name": "Option",
+                        "typeArguments": [balance(0)],
+                    }}},
+                    { "name": "rewards", "type": { "Vector": balance(1) } },
+                    { "name": "bonus", "type": { "Struct": {
+                        "address": "0x1",
+                        "module": "option",
+                        "name": "Option",
+                        "typeArguments": [{ "Struct": {
+                            "address": "0x2",
+                            "module": "coin",
+                            "name": "Coin",
+                            "typeArguments": [{ "TypeParameter": 0 }],
+                        }}],
+                    }}},
+                ])));
+            })
+            .await;
+        let vault_layout = server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .json_body_partial(r#"{"method": "sui_getNormalizedMoveStruct"}"#)
+                    .body_contains(r#""Vault""#);
+                then.status(200).json_body(normalized_struct(json!([
+                    { "name": "fees", "type": { "Struct": {
+                        "address": "0x2",
+                        "module": "coin",
+                        "name": "Coin",
+                        "typeArguments": [{ "TypeParameter": 0 }],
+                    }}},
+                    { "name": "count", "type": "U64" },
+                ])));
+            })
+            .await;
+
+        (pool_layout, vault_layout)
+    }
+
+    #[tokio::test]
+    async fn walks_object_fields_for_balances_and_coins() {
+        if !ensure_socket_permission() {
+            eprintln!("skipping walks_object_fields_for_balances_and_coins: binding to localhost is not permitted in this environment");
+            return;
+        }
+
+        let server = MockServer::start_async().await;
+        let (pool_layout, vault_layout) = mock_pool_transaction(&server).await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .json_body_partial(r#"{"method": "suix_getCoinMetadata"}"#);
+                then.status(200)
+                    .json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": { "decimals": 9 } }));
+            })
+            .await;
+        let client = TxParseClient::new(server.base_url());
+        let changes = client
+            .get_object_balance_changes("SWAPTX", "0xa001")
+            .await
+            .expect("object changes should load");
+
+        // Layouts are fetched once per struct, not per version.
+        pool_layout.assert_hits_async(1).await;
+        vault_layout.assert_hits_async(1).await;
+        let summary: Vec<(&str, u64, i128)> = changes
+            .iter()
+            .map(|change| {
+                (
+                    change.field_path.as_str(),
+                    u64::try_from(&change.current_value).expect("value should fit"),
+                    i128::try_from(&change.value_diff).expect("diff should fit"),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("locked", 7, 7),
+                ("reserve_x", 1100, 100),
+                ("rewards[1]", 3, 1),
+                ("vault.fees", 16, 6),
+            ]
+        );
+        let coins: Vec<&str> = changes
+            .iter()
+            .map(|change| change.coin_type.as_str())
+            .collect();
+        assert_eq!(
+            coins,
+            vec![
+                "0x2::sui::SUI",
+                "0x2::sui::SUI",
+                "0xdef::usdc::USDC",
+                "0x2::sui::SUI"
+            ]
+        );
+
+        let err = client
+            .get_object_balance_changes("SWAPTX", "0xa002")
+            .await
+            .expect_err("untouched object should fail");
+        assert!(matches!(
+            err,
+            ClientError::ObjectNotInTransaction { ref object_id, .. } if object_id == "0xa002"
+        ));
+    }
+
+    #[tokio::test]
+    async fn reports_every_coin_with_unknown_decimals_in_object_fields() {
+        if !ensure_socket_permission() {
+            eprintln!("skipping reports_every_coin_with_unknown_decimals_in_object_fields: binding to localhost is not permitted in this environment");
+            return;
+        }
+
+        let server = MockServer::start_async().await;
+        mock_pool_transaction(&server).await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .json_body_partial(r#"{"method": "suix_getCoinMetadata"}"#)
+                    .body_contains("0x2::sui::SUI");
+                then.status(200)
+                    .json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": { "decimals": 9 } }));
+            })
+            .await;
+        let usdc = server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .json_body_partial(r#"{"method": "suix_getCoinMetadata"}"#)
+                    .body_contains("usdc::USDC");
+                then.status(200)
+                    .json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": null }));
+            })
+            .await;
+
+        let client = TxParseClient::new(server.base_url());
+        let err = client
+            .get_object_balance_changes("SWAPTX", "0xa001")
+            .await
+            .expect_err("unknown decimals should fail");
+
+        usdc.assert_async().await;
+        assert!(matches!(
+            err,
+            ClientError::IncompleteBalanceData(ref failures) if failures == &vec![ObjectFailure {
+                object_id: "0xa001".to_string(),
+                reason: ObjectFailureReason::UnknownDecimals {
+                    coin_type: "0xdef::usdc::USDC".to_string(),
+                    reason: "coin has no metadata".to_string(),
+                },
+            }]
+        ));
+    }
+}
@@ -1,19 +1,25 @@
 use reqwest::Client;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
-use crate::parse::{parse_transaction, ParseError};
-use crate::types::{
-    CoinMetadata, DynamicFieldBalanceChange, ObjectChange, ObjectChangesResponse, ObjectOwner,
-    ParseResult, PastObjectResponse, TransactionBlockResponse,
-};
+use crate::coin_metadata::{cache_key, CoinMetadata, CoinMetadataCache, DecimalsRegistry};
+use crate::endpoint::{is_endpoint_failure, EndpointPool, SelectionStrategy};
+use crate::parse::{parse_transaction_with_options, ParseError, ParseOptions};
+use crate::rate_limit::RateLimit;
+use crate::retry::RetryPolicy;
+use crate::types::{ObjectFailure, ParseResult, RawCoinMetadata, TransactionBlockResponse};
 
 #[derive(Debug, Error)]
 pub enum ClientError {
     #[error("http transport error: {0}")]
     Http(#[from] reqwest::Error),
+    #[error("http status {status}")]
+    HttpStatus { status: u16 },
     #[error("rpc error {code}: {message}")]
     Rpc {
         code: i64,
@@ -22,207 +28,351 @@ pub enum ClientError {
     },
     #[error("rpc response missing result field")]
     MissingResult,
+    #[error("transaction {0} was not returned by the node")]
+    TransactionNotFound(String),
+    #[error("node returned an error for transaction {digest}: {error}")]
+    TransactionError { digest: String, error: Value },
+    #[error("batch request failed: {0}")]
+    BatchFailed(Arc<ClientError>),
+    #[error("object {object_id} was not changed by transaction {tx_digest}")]
+    ObjectNotInTransaction { tx_digest: String, object_id: String },
+    #[error("invalid price {value:?} for {coin_type}")]
+    InvalidPrice { coin_type: String, value: String },
+    #[error("decimals of {coin_type} are unknown: {reason}")]
+    UnknownDecimals { coin_type: String, reason: String },
+    #[error("{} object(s) could not be processed", .0.len())]
+    IncompleteBalanceData(Vec<ObjectFailure>),
     #[error(transparent)]
     Parse(#[from] ParseError),
 }
 
+/// Maximum number of digests the fullnode accepts in one
+/// `sui_multiGetTransactionBlocks` call.
+const MAX_MULTI_GET_TRANSACTIONS: usize = 50;
+
+/// JSON-RPC "invalid params" code, which the fullnode returns for the whole
+/// batch when a digest is malformed or unknown.
+const INVALID_PARAMS_CODE: i64 = -32602;
+
+/// How long an endpoint is avoided after a transport or rate-limit failure.
+const DEFAULT_ENDPOINT_COOLDOWN: Duration = Duration::from_secs(30);
+
 #[derive(Debug, Clone)]
 pub struct TxParseClient {
-    rpc_url: String,
+    endpoints: Arc<EndpointPool>,
     http: Client,
+    retry: RetryPolicy,
+    timeout: Option<Duration>,
+    coin_metadata: Arc<CoinMetadataCache>,
+    decimals_registry: Option<Arc<dyn DecimalsRegistry>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TxParseClientBuilder {
+    rpc_urls: Vec<(String, Option<RateLimit>)>,
+    rate_limit: Option<RateLimit>,
+    http: Option<Client>,
+    retry: RetryPolicy,
+    timeout: Option<Duration>,
+    selection: SelectionStrategy,
+    endpoint_cooldown: Duration,
+    coin_metadata: Option<Arc<CoinMetadataCache>>,
+    decimals_registry: Option<Arc<dyn DecimalsRegistry>>,
+}
+
+impl TxParseClientBuilder {
+    /// Adds a fallback fullnode. Requests fail over to other endpoints on
+    /// transport errors, timeouts and HTTP 429 or 5xx responses that the
+    /// retry policy retries; JSON-RPC errors are retried on the same node.
+    pub fn endpoint(mut self, rpc_url: impl Into<String>) -> Self {
+        self.rpc_urls.push((rpc_url.into(), None));
+        self
+    }
+
+    /// Adds a fallback fullnode with its own request budget instead of the
+    /// one set through [`rate_limit`](Self::rate_limit).
+    pub fn endpoint_with_rate_limit(mut self, rpc_url: impl Into<String>, limit: RateLimit) -> Self {
+        self.rpc_urls.push((rpc_url.into(), Some(limit)));
+        self
+    }
+
+    pub fn endpoints<I, S>(mut self, rpc_urls: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.rpc_urls
+            .extend(rpc_urls.into_iter().map(|rpc_url| (rpc_url.into(), None)));
+        self
+    }
+
+    /// Request budget applied to each endpoint that has none of its own.
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
+    pub fn selection_strategy(mut self, selection: SelectionStrategy) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    pub fn endpoint_cooldown(mut self, cooldown: Duration) -> Self {
+        self.endpoint_cooldown = cooldown;
+        self
+    }
+
+    pub fn http_client(mut self, http: Client) -> Self {
+        self.http = Some(http);
+        self
+    }
+
+    /// Enables retries, e.g. with [`RetryPolicy::default`]. Without a policy
+    /// every request is sent once.
+    pub fn retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Timeout applied to each individual request attempt.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Uses `cache` for coin metadata, e.g. one that is pre-seeded, backed by
+    /// a file or shared with other clients. Each client otherwise starts with
+    /// an empty cache of its own.
+    pub fn coin_metadata_cache(mut self, cache: Arc<CoinMetadataCache>) -> Self {
+        self.coin_metadata = Some(cache);
+        self
+    }
+
+    /// Decimals to fall back on when the node has no usable metadata for a
+    /// coin. Without one, such coins fail with
+    /// [`ClientError::UnknownDecimals`].
+    pub fn decimals_registry(mut self, registry: Arc<dyn DecimalsRegistry>) -> Self {
+        self.decimals_registry = Some(registry);
+        self
+    }
+
+    pub fn build(self) -> TxParseClient {
+        TxParseClient {
+            endpoints: Arc::new(EndpointPool::new(
+                self.rpc_urls
+                    .into_iter()
+                    .map(|(rpc_url, limit)| (rpc_url, limit.or(self.rate_limit)))
+                    .collect(),
+                self.selection,
+                self.endpoint_cooldown,
+            )),
+            http: self.http.unwrap_or_default(),
+            retry: self.retry,
+            timeout: self.timeout,
+            coin_metadata: self.coin_metadata.unwrap_or_default(),
+            decimals_registry: self.decimals_registry,
+        }
+    }
 }
 
 impl TxParseClient {
     pub fn new(rpc_url: impl Into<String>) -> Self {
-        Self {
-            rpc_url: rpc_url.into(),
-            http: Client::new(),
-        }
+        Self::builder(rpc_url).build()
     }
 
     pub fn with_http_client(rpc_url: impl Into<String>, http: Client) -> Self {
-        Self {
-            rpc_url: rpc_url.into(),
-            http,
+        Self::builder(rpc_url).http_client(http).build()
+    }
+
+    pub fn builder(rpc_url: impl Into<String>) -> TxParseClientBuilder {
+        TxParseClientBuilder {
+            rpc_urls: vec![(rpc_url.into(), None)],
+            rate_limit: None,
+            http: None,
+            retry: RetryPolicy::none(),
+            timeout: None,
+            selection: SelectionStrategy::default(),
+            endpoint_cooldown: DEFAULT_ENDPOINT_COOLDOWN,
+            coin_metadata: None,
+            decimals_registry: None,
         }
     }
 
+    pub fn coin_metadata_cache(&self) -> &Arc<CoinMetadataCache> {
+        &self.coin_metadata
+    }
+
     pub async fn parse_transaction(&self, digest: &str) -> Result<ParseResult, ClientError> {
-        let payload = json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "sui_getTransactionBlock",
-            "params": [
-                digest,
-                {
-                    "showBalanceChanges": true,
-                    "showEvents": true,
-                    "showEffects": true,
-                },
-            ],
-        });
+        self.parse_transaction_with_options(digest, &ParseOptions::default())
+            .await
+    }
 
-        let response = self
-            .http
-            .post(&self.rpc_url)
-            .json(&payload)
-            .send()
+    pub async fn parse_transaction_with_options(
+        &self,
+        digest: &str,
+        options: &ParseOptions,
+    ) -> Result<ParseResult, ClientError> {
+        let result: TransactionBlockResponse = self
+            .call("sui_getTransactionBlock", json!([digest, show_options(options)]))
             .await?;
-
-        let rpc_response: RpcResponse<TransactionBlockResponse> = response.json().await?;
-
-        if let Some(error) = rpc_response.error {
-            return Err(ClientError::Rpc {
-                code: error.code,
-                message: error.message,
-                data: error.data,
-            });
+        let mut parsed = parse_transaction_with_options(&result, options)?;
+        if options.coin_metadata {
+            self.attach_coin_metadata([&mut parsed]).await;
         }
 
-        let result = rpc_response.result.ok_or(ClientError::MissingResult)?;
-        let parsed = parse_transaction(&result)?;
-
         Ok(parsed)
     }
 
-    pub async fn get_bag_dynamic_field_balance_changes(
+    /// Parses many transactions using `sui_multiGetTransactionBlocks`, split
+    /// into chunks the node accepts. Results are returned in the order of
+    /// `digests`, so a missing or malformed transaction only fails its own
+    /// entry.
+    pub async fn parse_transactions(
         &self,
-        tx_digest: &str,
-        bag_id: &str,
-    ) -> Result<Vec<DynamicFieldBalanceChange>, ClientError> {
-        // Step 1: Get transaction block with objectChanges
-        let payload = json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "sui_getTransactionBlock",
-            "params": [
-                tx_digest,
-                {
-                    "showObjectChanges": true,
-                },
-            ],
-        });
+        digests: &[&str],
+    ) -> Vec<Result<ParseResult, ClientError>> {
+        self.parse_transactions_with_options(digests, &ParseOptions::default())
+            .await
+    }
 
-        let response = self.http.post(&self.rpc_url).json(&payload).send().await?;
-        let rpc_response: RpcResponse<ObjectChangesResponse> = response.json().await?;
+    pub async fn parse_transactions_with_options(
+        &self,
+        digests: &[&str],
+        options: &ParseOptions,
+    ) -> Vec<Result<ParseResult, ClientError>> {
+        let mut results = Vec::with_capacity(digests.len());
+
+        for chunk in digests.chunks(MAX_MULTI_GET_TRANSACTIONS) {
+            let response: Result<Vec<Value>, ClientError> = self
+                .call("sui_multiGetTransactionBlocks", json!([chunk, show_options(options)]))
+                .await;
+
+            match response {
+                Ok(entries) => {
+                    // Entries normally carry their digest; fall back to the
+                    // request order for error entries that do not.
+                    let in_order = entries.len() == chunk.len();
+                    let by_digest: HashMap<&str, &Value> = entries
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(index, entry)| {
+                            let digest = match entry.get("digest").and_then(Value::as_str) {
+                                Some(digest) => digest,
+                                None if in_order => chunk[index],
+                                None => return None,
+                            };
+                            Some((digest, entry))
+                        })
+                        .collect();
+
+                    for digest in chunk {
+                        results.push(match by_digest.get(digest) {
+                            Some(entry) => match entry.get("error") {
+                                Some(error) => Err(ClientError::TransactionError {
+                                    digest: digest.to_string(),
+                                    error: error.clone(),
+                                }),
+                                None => TransactionBlockResponse::deserialize(*entry)
+                                    .map_err(ParseError::from)
+                                    .and_then(|response| parse_transaction_with_options(&response, options))
+                                    .map_err(ClientError::from),
+                            },
+                            None => Err(ClientError::TransactionNotFound(digest.to_string())),
+                        });
+                    }
+                }
+                // The node rejects the whole call when any digest is unknown
+                // or malformed, so fall back to one request per digest to
+                // isolate it.
+                Err(ClientError::Rpc { code: INVALID_PARAMS_CODE, .. }) => {
+                    // Metadata is attached to the whole batch below.
+                    let single = ParseOptions { coin_metadata: false, ..options.clone() };
+                    for digest in chunk {
+                        results.push(self.parse_transaction_with_options(digest, &single).await);
+                    }
+                }
+                // Anything else, e.g. throttling or an unreachable node, would
+                // fail each single request just the same.
+                Err(error) => {
+                    let error = Arc::new(error);
+                    results.extend(chunk.iter().map(|_| Err(ClientError::BatchFailed(error.clone()))));
+                }
+            }
+        }
 
-        if let Some(error) = rpc_response.error {
-            return Err(ClientError::Rpc {
-                code: error.code,
-                message: error.message,
-                data: error.data,
-            });
+        if options.coin_metadata {
+            self.attach_coin_metadata(results.iter_mut().flatten()).await;
         }
+        results
+    }
 
-        let result = rpc_response.result.ok_or(ClientError::MissingResult)?;
-        let object_changes = result.object_changes.unwrap_or_default();
+    pub(crate) async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T, ClientError> {
+        let payload = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
 
-        // Step 2: Filter objects owned by the bag
-        let bag_owned_objects: Vec<_> = object_changes
-            .iter()
-            .filter_map(|change| match change {
-                ObjectChange::Created { object_id, version, owner }
-                | ObjectChange::Mutated { object_id, version, owner, .. } => {
-                    if let Some(ObjectOwner::ObjectOwner { object_owner }) = owner {
-                        if object_owner == bag_id {
-                            return Some((object_id.clone(), version.clone(),
-                                match change {
-                                    ObjectChange::Mutated { previous_version, .. } => Some(previous_version.clone()),
-                                    _ => None,
-                                }
-                            ));
+        // Failing over to an endpoint not yet tried for this request is
+        // immediate; only repeated attempts count against the retry policy.
+        // Errors the node answered with are retried where they came from.
+        let mut tried = Vec::new();
+        let mut same_endpoint = None;
+        let mut attempt = 1;
+        loop {
+            let index = same_endpoint
+                .take()
+                .unwrap_or_else(|| self.endpoints.select(&tried));
+            let permit = self.endpoints.acquire(index).await;
+            let started = Instant::now();
+            let result = self.send(self.endpoints.url(index), &payload).await;
+            drop(permit);
+
+            match result {
+                Err(error) if self.retry.should_retry(&error) => {
+                    if is_endpoint_failure(&error) {
+                        self.endpoints.record_failure(index);
+                        if !tried.contains(&index) {
+                            tried.push(index);
                         }
+                        if self.endpoints.has_untried(&tried) {
+                            continue;
+                        }
+                    } else {
+                        same_endpoint = Some(index);
+                    }
+                    if attempt >= self.retry.max_attempts {
+                        return Err(error);
                     }
-                    None
+                    tokio::time::sleep(self.retry.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                Err(error) if is_endpoint_failure(&error) => {
+                    self.endpoints.record_failure(index);
+                    return Err(error);
+                }
+                result => {
+                    self.endpoints.record_success(index, started.elapsed());
+                    return result;
                 }
-                _ => None,
-            })
-            .collect();
-
-        // Step 3: Process each object
-        let mut balance_changes = Vec::new();
-        let mut coin_metadata_cache: HashMap<String, u8> = HashMap::new();
-
-        for (object_id, version, previous_version) in bag_owned_objects {
-            match self.process_object(&object_id, &version, previous_version.as_deref(), &mut coin_metadata_cache).await {
-                Ok(Some(change)) => balance_changes.push(change),
-                Ok(None) => {},
-                Err(e) => eprintln!("Error processing object {}: {:?}", object_id, e),
             }
         }
-
-        Ok(balance_changes)
     }
 
-    async fn process_object(
-        &self,
-        object_id: &str,
-        version: &str,
-        previous_version: Option<&str>,
-        coin_metadata_cache: &mut HashMap<String, u8>,
-    ) -> Result<Option<DynamicFieldBalanceChange>, ClientError> {
-        // Get current version
-        let current_obj = self.get_past_object(object_id, version).await?;
-
-        // Get previous version
-        let previous_obj = if let Some(prev_ver) = previous_version {
-            Some(self.get_past_object(object_id, prev_ver).await?)
-        } else {
-            None
-        };
-
-        // Extract balance values
-        let current_value = self.extract_balance_value(&current_obj);
-        let previous_value = previous_obj.as_ref().and_then(|obj| self.extract_balance_value(obj));
-
-        if let Some(curr_val) = current_value {
-            let prev_val = previous_value.unwrap_or(0);
-            let diff = curr_val as i128 - prev_val as i128;
-
-            // Extract coin type
-            let object_type = self.extract_object_type(&current_obj).unwrap_or_default();
-            let coin_type = self.extract_coin_type(&object_type);
-
-            // Get decimals
-            let decimals = if let Some(&cached) = coin_metadata_cache.get(&coin_type) {
-                cached
-            } else {
-                let dec = self.get_coin_decimals(&coin_type).await?;
-                coin_metadata_cache.insert(coin_type.clone(), dec);
-                dec
-            };
-
-            return Ok(Some(DynamicFieldBalanceChange {
-                coin_type,
-                previous_value: prev_val.to_string(),
-                current_value: curr_val.to_string(),
-                value_diff: diff.to_string(),
-                decimals,
-            }));
+    async fn send<T: DeserializeOwned>(&self, url: &str, payload: &Value) -> Result<T, ClientError> {
+        let mut request = self.http.post(url).json(payload);
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
         }
 
-        Ok(None)
-    }
-
-    async fn get_past_object(&self, object_id: &str, version: &str) -> Result<PastObjectResponse, ClientError> {
-        // Parse version as integer
-        let version_int: u64 = version.parse().unwrap_or(0);
-
-        let payload = json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "sui_tryGetPastObject",
-            "params": [
-                object_id,
-                version_int,
-                {
-                    "showContent": true,
-                },
-            ],
-        });
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ClientError::HttpStatus { status: status.as_u16() });
+        }
 
-        let response = self.http.post(&self.rpc_url).json(&payload).send().await?;
-        let rpc_response: RpcResponse<PastObjectResponse> = response.json().await?;
+        let rpc_response: RpcResponse<T> = response.json().await?;
 
         if let Some(error) = rpc_response.error {
             return Err(ClientError::Rpc {
@@ -235,123 +385,90 @@ impl TxParseClient {
         rpc_response.result.ok_or(ClientError::MissingResult)
     }
 
-    fn extract_balance_value(&self, obj: &PastObjectResponse) -> Option<u64> {
-        if obj.status != "VersionFound" {
-            return None;
-        }
-
-        let details = obj.details.as_ref()?;
-        let content = details.content.as_ref()?;
-
-        if content.data_type != "moveObject" {
-            return None;
+    /// Metadata of `coin_type`, from the cache when possible. Fetched
+    /// metadata is added to the cache but not written to its file; call
+    /// [`CoinMetadataCache::save`] to persist it. Returns `None` for coin
+    /// types without metadata.
+    pub async fn get_coin_metadata(&self, coin_type: &str) -> Result<Option<CoinMetadata>, ClientError> {
+        if let Some(metadata) = self.coin_metadata.get(coin_type) {
+            return Ok(Some(metadata));
         }
 
-        let fields = content.fields.as_ref()?;
+        let raw = match self.call::<Option<RawCoinMetadata>>("suix_getCoinMetadata", json!([coin_type])).await {
+            Ok(Some(raw)) => raw,
+            Ok(None) | Err(ClientError::MissingResult) => return Ok(None),
+            Err(error) => return Err(error),
+        };
+        let metadata = CoinMetadata {
+            decimals: raw.decimals,
+            symbol: raw.symbol.unwrap_or_default(),
+            name: raw.name.unwrap_or_default(),
+            icon_url: raw.icon_url.filter(|url| !url.is_empty()),
+        };
+        self.coin_metadata.insert(coin_type, metadata.clone());
 
-        // Try direct balance field
-        if let Some(balance) = fields.get("balance") {
-            if let Some(val) = balance.as_u64() {
-                return Some(val);
-            }
-            if let Some(s) = balance.as_str() {
-                if let Ok(val) = s.parse::<u64>() {
-                    return Some(val);
-                }
-            }
-            if let Some(obj) = balance.as_object() {
-                if let Some(val) = obj.get("value").and_then(|v| v.as_u64()) {
-                    return Some(val);
-                }
-            }
-        }
+        Ok(Some(metadata))
+    }
 
-        // Try value field (could be nested object like Coin<T>)
-        if let Some(value) = fields.get("value") {
-            if let Some(val) = value.as_u64() {
-                return Some(val);
-            }
-            if let Some(s) = value.as_str() {
-                if let Ok(val) = s.parse::<u64>() {
-                    return Some(val);
-                }
-            }
-            if let Some(obj) = value.as_object() {
-                if let Some(nested_fields) = obj.get("fields").and_then(|f| f.as_object()) {
-                    if let Some(val) = nested_fields.get("balance") {
-                        if let Some(num) = val.as_u64() {
-                            return Some(num);
-                        }
-                        if let Some(s) = val.as_str() {
-                            if let Ok(num) = s.parse::<u64>() {
-                                return Some(num);
-                            }
-                        }
+    /// Fills `coin_metadata` on every balance change of `results`, looking
+    /// up each coin type once. Coin types whose metadata cannot be loaded are
+    /// left without it, and are not asked for again by the same call.
+    async fn attach_coin_metadata<'a>(&self, results: impl IntoIterator<Item = &'a mut ParseResult>) {
+        let mut lookups: HashMap<String, Option<CoinMetadata>> = HashMap::new();
+        for parsed in results {
+            let changes = parsed
+                .balance_changes
+                .iter_mut()
+                .chain(parsed.transfer_balance_changes.iter_mut().flatten());
+            for change in changes {
+                let key = cache_key(&change.coin_type);
+                let metadata = match lookups.get(&key) {
+                    Some(metadata) => metadata.clone(),
+                    None => {
+                        let metadata = self.get_coin_metadata(&change.coin_type).await.ok().flatten();
+                        lookups.insert(key, metadata.clone());
+                        metadata
                     }
-                    if let Some(val) = nested_fields.get("value") {
-                        if let Some(num) = val.as_u64() {
-                            return Some(num);
-                        }
-                        if let Some(s) = val.as_str() {
-                            if let Ok(num) = s.parse::<u64>() {
-                                return Some(num);
-                            }
-                        }
-                    }
-                }
+                };
+                change.coin_metadata = metadata;
             }
         }
-
-        None
     }
 
-    fn extract_object_type(&self, obj: &PastObjectResponse) -> Option<String> {
-        if obj.status != "VersionFound" {
-            return None;
+    /// Decimals of `coin_type` from its metadata, or from the decimals
+    /// registry when the node has none or cannot be reached. Never guesses:
+    /// fails with [`ClientError::UnknownDecimals`] when the node has no
+    /// decimals and the registry does not know the coin, and with the error
+    /// of the metadata request when it failed and the registry does not
+    /// know the coin.
+    pub async fn get_coin_decimals(&self, coin_type: &str) -> Result<u8, ClientError> {
+        let metadata = self.get_coin_metadata(coin_type).await;
+        if let Ok(Some(CoinMetadata { decimals: Some(decimals), .. })) = metadata {
+            return Ok(decimals);
         }
-
-        obj.details
-            .as_ref()?
-            .content
-            .as_ref()?
-            .type_
-            .clone()
-    }
-
-    fn extract_coin_type(&self, type_str: &str) -> String {
-        // Extract coin type from patterns like:
-        // "0x2::coin::Coin<0x2::sui::SUI>"
-        // "0x2::dynamic_field::Field<0x1::type_name::TypeName, 0x2::coin::Coin<0x2::sui::SUI>>"
-        if let Some(captures) = regex::Regex::new(r"(?:coin::Coin|balance::Balance)<([^>]+)>")
-            .ok()
-            .and_then(|re| re.captures(type_str))
-        {
-            if let Some(coin_type) = captures.get(1) {
-                return coin_type.as_str().to_string();
-            }
+        if let Some(decimals) = self.decimals_registry.as_ref().and_then(|registry| registry.decimals(coin_type)) {
+            return Ok(decimals);
         }
 
-        type_str.to_string()
+        let reason = match metadata? {
+            Some(_) => "metadata has no decimals",
+            None => "coin has no metadata",
+        };
+        Err(ClientError::UnknownDecimals { coin_type: coin_type.to_string(), reason: reason.to_string() })
     }
+}
 
-    async fn get_coin_decimals(&self, coin_type: &str) -> Result<u8, ClientError> {
-        let payload = json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "suix_getCoinMetadata",
-            "params": [coin_type],
-        });
-
-        let response = self.http.post(&self.rpc_url).json(&payload).send().await?;
-        let rpc_response: RpcResponse<CoinMetadata> = response.json().await?;
-
-        if let Some(error) = rpc_response.error {
-            eprintln!("Failed to get decimals for {}: {:?}", coin_type, error);
-            return Ok(0);
-        }
-
-        Ok(rpc_response.result.and_then(|m| m.decimals).unwrap_or(0))
+fn show_options(options: &ParseOptions) -> Value {
+    let mut show = json!({
+        "showInput": true,
+        "showBalanceChanges": true,
+        "showEvents": true,
+        "showEffects": true,
+    });
+    if options.object_changes {
+        show["showObjectChanges"] = Value::Bool(true);
     }
+    show
 }
 
 #[derive(Debug, Deserialize)]
@@ -374,6 +491,7 @@ struct RpcError {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::coin_metadata::StaticDecimalsRegistry;
     use httpmock::MockServer;
     use httpmock::Method::POST;
     use std::net::TcpListener;
@@ -411,6 +529,7 @@ mod tests {
                     "params": [
                         "0xdead",
                         {
+                            "showInput": true,
                             "showBalanceChanges": true,
                             "showEvents": true,
                             "showEffects": true,
@@ -428,6 +547,428 @@ mod tests {
         assert_eq!(result.balance_changes[0].owner, "0x6f4d3a");
     }
 
+    #[tokio::test]
+    async fn attaches_coin_metadata_from_a_shared_cache() {
+        if !ensure_socket_permission() {
+            eprintln!("skipping attaches_coin_metadata_from_a_shared_cache: binding to localhost is not permitted in this environment");
+            return;
+        }
+
+        let server = MockServer::start_async().await;
+        server.mock_async(|when, then| {
+            when.method(POST)
+                .json_body_partial(r#"{"method": "sui_getTransactionBlock"}"#);
+            then.status(200).json_body(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": load_fixture_body(),
+            }));
+        }).await;
+        let metadata = server.mock_async(|when, then| {
+            when.method(POST)
+                .json_body_partial(r#"{"method": "suix_getCoinMetadata"}"#);
+            then.status(200).json_body(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": { "decimals": 6, "symbol": "TKN", "name": "Token", "iconUrl": "" },
+            }));
+        }).await;
+
+        let cache = Arc::new(CoinMetadataCache::new());
+        let sui = CoinMetadata {
+            decimals: Some(9),
+            symbol: "SUI".to_string(),
+            name: "Sui".to_string(),
+            icon_url: None,
+        };
+        cache.insert("0x2::sui::SUI", sui.clone());
+        let client = TxParseClient::builder(server.base_url())
+            .coin_metadata_cache(cache.clone())
+            .build();
+        let options = ParseOptions { coin_metadata: true, ..ParseOptions::default() };
+
+        let first = client.parse_transaction_with_options("0xdead", &options).await.expect("call should succeed");
+        let second = client.clone().parse_transaction_with_options("0xdead", &options).await.expect("call should succeed");
+
+        // SUI was pre-seeded and the other four coin types are fetched once.
+        metadata.assert_hits_async(4).await;
+        assert_eq!(first, second);
+        assert_eq!(first.balance_changes[0].coin_metadata, Some(sui));
+        let token = first.balance_changes[1].coin_metadata.as_ref().expect("metadata should be attached");
+        assert_eq!((token.decimals, token.symbol.as_str(), token.icon_url.as_deref()), (Some(6), "TKN", None));
+        assert_eq!(cache.get("0x2::other::COIN").and_then(|metadata| metadata.decimals), Some(6));
+    }
+
+    #[tokio::test]
+    async fn reports_unknown_decimals_unless_the_registry_knows_them() {
+        if !ensure_socket_permission() {
+            eprintln!("skipping reports_unknown_decimals_unless_the_registry_knows_them: binding to localhost is not permitted in this environment");
+            return;
+        }
+
+        let server = MockServer::start_async().await;
+        server.mock_async(|when, then| {
+            when.method(POST)
+                .json_body_partial(r#"{"method": "suix_getCoinMetadata"}"#)
+                .body_contains("0x2::sui::SUI");
+            then.status(200).json_body(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": { "code": -32000, "message": "node is overloaded" },
+            }));
+        }).await;
+        server.mock_async(|when, then| {
+            when.method(POST)
+                .json_body_partial(r#"{"method": "suix_getCoinMetadata"}"#)
+                .body_contains("0xabc::lp::LP");
+            then.status(200).json_body(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": { "symbol": "LP", "name": "LP Token" },
+            }));
+        }).await;
+
+        let client = TxParseClient::builder(server.base_url()).retry_policy(RetryPolicy::none()).build();
+        let error = client.get_coin_decimals("0x2::sui::SUI").await.expect_err("metadata request should fail");
+        assert!(matches!(&error, ClientError::Rpc { message, .. } if message == "node is overloaded"));
+        assert!(matches!(
+            client.get_coin_decimals("0xabc::lp::LP").await,
+            Err(ClientError::UnknownDecimals { ref coin_type, ref reason })
+                if coin_type == "0xabc::lp::LP" && reason == "metadata has no decimals"
+        ));
+
+        let registry = StaticDecimalsRegistry::well_known().with("0xabc::lp::LP", 0);
+        let client = TxParseClient::builder(server.base_url())
+            .retry_policy(RetryPolicy::none())
+            .decimals_registry(Arc::new(registry))
+            .build();
+        assert_eq!(client.get_coin_decimals("0x2::sui::SUI").await.expect("registry should answer"), 9);
+        assert_eq!(client.get_coin_decimals("0xabc::lp::LP").await.expect("registry should answer"), 0);
+    }
+
+    #[tokio::test]
+    async fn parses_batches_and_reports_missing_digests() {
+        if !ensure_socket_permission() {
+            eprintln!("skipping parses_batches_and_reports_missing_digests: binding to localhost is not permitted in this environment");
+            return;
+        }
+
+        let server = MockServer::start_async().await;
+        let mock = server.mock_async(|when, then| {
+            when.method(POST)
+                .json_body_partial(r#"{"method": "sui_multiGetTransactionBlocks", "params": [["8oCTESTTXDIGEST", "0xmissing"]]}"#);
+            then.status(200).json_body(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": [load_fixture_body()],
+            }));
+        }).await;
+
+        let client = TxParseClient::new(server.base_url());
+        let results = client.parse_transactions(&["8oCTESTTXDIGEST", "0xmissing"]).await;
+
+        mock.assert_async().await;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_ref().expect("first digest should parse").balance_changes.len(), 5);
+        assert!(matches!(&results[1], Err(ClientError::TransactionNotFound(digest)) if digest == "0xmissing"));
+    }
+
+    #[tokio::test]
+    async fn splits_batches_and_falls_back_on_rpc_errors() {
+        if !ensure_socket_permission() {
+            eprintln!("skipping splits_batches_and_falls_back_on_rpc_errors: binding to localhost is not permitted in this environment");
+            return;
+        }
+
+        let server = MockServer::start_async().await;
+        let batch = server.mock_async(|when, then| {
+            when.method(POST)
+                .json_body_partial(r#"{"method": "sui_multiGetTransactionBlocks"}"#);
+            then.status(200).json_body(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": {
+                    "code": -32602,
+                    "message": "Could not find the referenced transaction",
+                }
+            }));
+        }).await;
+        let single = server.mock_async(|when, then| {
+            when.method(POST)
+                .json_body_partial(r#"{"method": "sui_getTransactionBlock"}"#);
+            then.status(200).json_body(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": load_fixture_body(),
+            }));
+        }).await;
+
+        let digests: Vec<String> = (0..MAX_MULTI_GET_TRANSACTIONS + 1).map(|i| format!("0x{i:x}")).collect();
+        let digests: Vec<&str> = digests.iter().map(String::as_str).collect();
+        let client = TxParseClient::new(server.base_url());
+        let results = client.parse_transactions(&digests).await;
+
+        batch.assert_hits_async(2).await;
+        single.assert_hits_async(digests.len()).await;
+        assert_eq!(results.len(), digests.len());
+        assert!(results.iter().all(Result::is_ok));
+    }
+
+    #[tokio::test]
+    async fn looks_up_coin_metadata_once_per_coin_type_in_a_batch() {
+        if !ensure_socket_permission() {
+            eprintln!("skipping looks_up_coin_metadata_once_per_coin_type_in_a_batch: binding to localhost is not permitted in this environment");
+            return;
+        }
+
+        let server = MockServer::start_async().await;
+        server.mock_async(|when, then| {
+            when.method(POST)
+                .json_body_partial(r#"{"method": "sui_multiGetTransactionBlocks"}"#);
+            then.status(200).json_body(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": { "code": -32602, "message": "Could not find the referenced transaction" },
+            }));
+        }).await;
+        server.mock_async(|when, then| {
+            when.method(POST)
+                .json_body_partial(r#"{"method": "sui_getTransactionBlock"}"#);
+            then.status(200).json_body(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": load_fixture_body(),
+            }));
+        }).await;
+        let metadata = server.mock_async(|when, then| {
+            when.method(POST)
+                .json_body_partial(r#"{"method": "suix_getCoinMetadata"}"#);
+            then.status(200).json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": null }));
+        }).await;
+
+        let client = TxParseClient::new(server.base_url());
+        let options = ParseOptions { coin_metadata: true, ..ParseOptions::default() };
+        let results = client.parse_transactions_with_options(&["0xa", "0xb", "0xc"], &options).await;
+
+        // Five coin types without metadata, asked for once each across the
+        // three transactions.
+        metadata.assert_hits_async(5).await;
+        assert!(results.iter().all(Result::is_ok));
+        assert!(results
+            .iter()
+            .flatten()
+            .flat_map(|parsed| &parsed.balance_changes)
+            .all(|change| change.coin_metadata.is_none()));
+    }
+
+    #[tokio::test]
+    async fn keeps_per_entry_errors_and_fails_chunks_on_other_errors() {
+        if !ensure_socket_permission() {
+            eprintln!("skipping keeps_per_entry_errors_and_fails_chunks_on_other_errors: binding to localhost is not permitted in this environment");
+            return;
+        }
+
+        let server = MockServer::start_async().await;
+        let pruned = json!({ "code": "transactionPruned", "message": "transaction data was pruned" });
+        server.mock_async(|when, then| {
+            when.method(POST)
+                .json_body_partial(r#"{"method": "sui_multiGetTransactionBlocks", "params": [["8oCTESTTXDIGEST", "0xpruned"]]}"#);
+            then.status(200).json_body(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": [load_fixture_body(), { "digest": "0xpruned", "error": pruned }],
+            }));
+        }).await;
+        let throttled = server.mock_async(|when, then| {
+            when.method(POST)
+                .json_body_partial(r#"{"method": "sui_multiGetTransactionBlocks", "params": [["0xa", "0xb"]]}"#);
+            then.status(429).body("too many requests");
+        }).await;
+        let single = server.mock_async(|when, then| {
+            when.method(POST)
+                .json_body_partial(r#"{"method": "sui_getTransactionBlock"}"#);
+            then.status(200).json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": load_fixture_body() }));
+        }).await;
+
+        let client = TxParseClient::builder(server.base_url()).retry_policy(RetryPolicy::none()).build();
+        let results = client.parse_transactions(&["8oCTESTTXDIGEST", "0xpruned"]).await;
+        assert!(results[0].is_ok());
+        assert!(matches!(
+            &results[1],
+            Err(ClientError::TransactionError { digest, error }) if digest == "0xpruned" && *error == pruned
+        ));
+
+        let results = client.parse_transactions(&["0xa", "0xb"]).await;
+        throttled.assert_hits_async(1).await;
+        single.assert_hits_async(0).await;
+        assert!(results.iter().all(|result| matches!(
+            result,
+            Err(ClientError::BatchFailed(error)) if matches!(**error, ClientError::HttpStatus { status: 429 })
+        )));
+    }
+
+    fn fast_retry_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff: Duration::from_millis(1),
+            jitter: false,
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_retryable_statuses_and_rpc_codes() {
+        if !ensure_socket_permission() {
+            eprintln!("skipping retries_retryable_statuses_and_rpc_codes: binding to localhost is not permitted in this environment");
+            return;
+        }
+
+        let server = MockServer::start_async().await;
+        let unavailable = server.mock_async(|when, then| {
+            when.method(POST).json_body_partial(r#"{"params": ["0xbusy"]}"#);
+            then.status(503).body("upstream unavailable");
+        }).await;
+        let internal = server.mock_async(|when, then| {
+            when.method(POST).json_body_partial(r#"{"params": ["0xflaky"]}"#);
+            then.status(200).json_body(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": { "code": -32603, "message": "internal error" }
+            }));
+        }).await;
+        let bad_request = server.mock_async(|when, then| {
+            when.method(POST).json_body_partial(r#"{"params": ["0xbad"]}"#);
+            then.status(400).body("bad request");
+        }).await;
+
+        let client = TxParseClient::builder(server.base_url())
+            .retry_policy(fast_retry_policy(3))
+            .build();
+        let busy = client.call::<Value>("sui_getTransactionBlock", json!(["0xbusy"])).await;
+        let flaky = client.call::<Value>("sui_getTransactionBlock", json!(["0xflaky"])).await;
+        let bad = client.call::<Value>("sui_getTransactionBlock", json!(["0xbad"])).await;
+
+        unavailable.assert_hits_async(3).await;
+        internal.assert_hits_async(3).await;
+        bad_request.assert_hits_async(1).await;
+        assert!(matches!(busy, Err(ClientError::HttpStatus { status: 503 })));
+        assert!(matches!(flaky, Err(ClientError::Rpc { code: -32603, .. })));
+        assert!(matches!(bad, Err(ClientError::HttpStatus { status: 400 })));
+    }
+
+    #[tokio::test]
+    async fn sends_requests_once_without_a_retry_policy() {
+        if !ensure_socket_permission() {
+            eprintln!("skipping sends_requests_once_without_a_retry_policy: binding to localhost is not permitted in this environment");
+            return;
+        }
+
+        let server = MockServer::start_async().await;
+        let mock = server.mock_async(|when, then| {
+            when.method(POST);
+            then.status(503).body("upstream unavailable");
+        }).await;
+
+        let client = TxParseClient::new(server.base_url());
+        let err = client.parse_transaction("0xbusy").await.expect_err("request should fail");
+
+        mock.assert_hits_async(1).await;
+        assert!(matches!(err, ClientError::HttpStatus { status: 503 }));
+    }
+
+    #[tokio::test]
+    async fn retries_requests_that_time_out() {
+        if !ensure_socket_permission() {
+            eprintln!("skipping retries_requests_that_time_out: binding to localhost is not permitted in this environment");
+            return;
+        }
+
+        let server = MockServer::start_async().await;
+        let mock = server.mock_async(|when, then| {
+            when.method(POST);
+            then.status(200)
+                .delay(Duration::from_millis(500))
+                .json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": load_fixture_body() }));
+        }).await;
+
+        let client = TxParseClient::builder(server.base_url())
+            .retry_policy(fast_retry_policy(2))
+            .timeout(Duration::from_millis(50))
+            .build();
+        let err = client
+            .parse_transaction("0xslow")
+            .await
+            .expect_err("request should time out");
+
+        mock.assert_hits_async(2).await;
+        match err {
+            ClientError::Http(error) => assert!(error.is_timeout()),
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn fails_over_to_healthy_endpoints() {
+        if !ensure_socket_permission() {
+            eprintln!("skipping fails_over_to_healthy_endpoints: binding to localhost is not permitted in this environment");
+            return;
+        }
+
+        let limited = MockServer::start_async().await;
+        let healthy = MockServer::start_async().await;
+        let limited_mock = limited.mock_async(|when, then| {
+            when.method(POST);
+            then.status(429).body("too many requests");
+        }).await;
+        let healthy_mock = healthy.mock_async(|when, then| {
+            when.method(POST);
+            then.status(200).json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": load_fixture_body() }));
+        }).await;
+
+        let client = TxParseClient::builder(limited.base_url())
+            .endpoint(healthy.base_url())
+            .retry_policy(RetryPolicy::none())
+            .build();
+        client.parse_transaction("0xfirst").await.expect("should fail over to the healthy endpoint");
+        client.parse_transaction("0xsecond").await.expect("should skip the rate-limited endpoint");
+
+        limited_mock.assert_hits_async(1).await;
+        healthy_mock.assert_hits_async(2).await;
+    }
+
+    #[tokio::test]
+    async fn retries_rpc_errors_on_the_same_endpoint() {
+        if !ensure_socket_permission() {
+            eprintln!("skipping retries_rpc_errors_on_the_same_endpoint: binding to localhost is not permitted in this environment");
+            return;
+        }
+
+        let failing = MockServer::start_async().await;
+        let other = MockServer::start_async().await;
+        let failing_mock = failing.mock_async(|when, then| {
+            when.method(POST);
+            then.status(200).json_body(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": { "code": -32603, "message": "internal error" }
+            }));
+        }).await;
+        let other_mock = other.mock_async(|when, then| {
+            when.method(POST);
+            then.status(200).json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": load_fixture_body() }));
+        }).await;
+
+        let client = TxParseClient::builder(failing.base_url())
+            .endpoint(other.base_url())
+            .retry_policy(fast_retry_policy(2))
+            .build();
+        let err = client.parse_transaction("0xflaky").await.expect_err("rpc error should be returned");
+
+        failing_mock.assert_hits_async(2).await;
+        other_mock.assert_hits_async(0).await;
+        assert!(matches!(err, ClientError::Rpc { code: -32603, .. }));
+    }
+
     #[tokio::test]
     async fn bubbles_up_rpc_errors() {
         if !ensure_socket_permission() {
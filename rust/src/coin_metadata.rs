@@ -0,0 +1,243 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+
+use crate::address::AddressFormat;
+use crate::type_tag::normalize_type;
+
+/// Display metadata of a coin type, as returned by `suix_getCoinMetadata`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoinMetadata {
+    /// `None` when the node's metadata omits the decimals.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decimals: Option<u8>,
+    pub symbol: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+}
+
+/// Coin metadata shared by all clones of a
+/// [`TxParseClient`](crate::TxParseClient), keyed by coin type.
+///
+/// Coin types are normalized, so `0x2::sui::SUI` and its long form share an
+/// entry. A cache created with [`with_file`](Self::with_file) is loaded from
+/// a JSON file and written back to it only by [`save`](Self::save).
+#[derive(Debug, Default)]
+pub struct CoinMetadataCache {
+    entries: RwLock<HashMap<String, CoinMetadata>>,
+    path: Option<PathBuf>,
+}
+
+impl CoinMetadataCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A cache backed by `path`. Existing entries are loaded if the file
+    /// exists; a missing file starts an empty cache.
+    pub fn with_file(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let entries: HashMap<String, CoinMetadata> = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(error) => return Err(error),
+        };
+
+        let cache = Self {
+            entries: RwLock::default(),
+            path: Some(path),
+        };
+        for (coin_type, metadata) in entries {
+            cache.insert(&coin_type, metadata);
+        }
+        Ok(cache)
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn get(&self, coin_type: &str) -> Option<CoinMetadata> {
+        self.entries
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(&cache_key(coin_type))
+            .cloned()
+    }
+
+    /// Adds or replaces an entry, e.g. to pre-seed well-known coins. Call
+    /// [`save`](Self::save) to persist it.
+    pub fn insert(&self, coin_type: &str, metadata: CoinMetadata) {
+        self.entries
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(cache_key(coin_type), metadata);
+    }
+
+    /// Writes all entries to the backing file. Does nothing for a cache
+    /// without one. Concurrent saves each write a complete file; the last
+    /// one to finish wins.
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let json = {
+            let entries = self
+                .entries
+                .read()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            serde_json::to_vec_pretty(&*entries)?
+        };
+        // Write to a sibling file first so a crash never leaves a truncated
+        // cache behind. The name is unique per save so that concurrent saves,
+        // from this process or another one, never rename each other's file.
+        static SAVES: AtomicU64 = AtomicU64::new(0);
+        let mut name = path.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(
+            ".{}.{}.tmp",
+            std::process::id(),
+            SAVES.fetch_add(1, Ordering::Relaxed)
+        ));
+        let temp = path.with_file_name(name);
+        if let Err(error) = std::fs::write(&temp, json) {
+            let _ = std::fs::remove_file(&temp);
+            return Err(error);
+        }
+        std::fs::rename(&temp, path).inspect_err(|_| {
+            let _ = std::fs::remove_file(&temp);
+        })
+    }
+}
+
+/// Source of decimals for coins whose metadata the node cannot provide.
+///
+/// Consulted by [`TxParseClient`](crate::TxParseClient) only after
+/// `suix_getCoinMetadata` fails or returns no decimals.
+pub trait DecimalsRegistry: fmt::Debug + Send + Sync {
+    fn decimals(&self, coin_type: &str) -> Option<u8>;
+}
+
+/// A fixed table of coin decimals keyed by normalized coin type.
+#[derive(Debug, Clone, Default)]
+pub struct StaticDecimalsRegistry {
+    decimals: HashMap<String, u8>,
+}
+
+impl StaticDecimalsRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// SUI and the mainnet native USDC.
+    pub fn well_known() -> Self {
+        Self::new().with("0x2::sui::SUI", 9).with(
+            "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC",
+            6,
+        )
+    }
+
+    pub fn with(mut self, coin_type: &str, decimals: u8) -> Self {
+        self.decimals.insert(cache_key(coin_type), decimals);
+        self
+    }
+}
+
+impl DecimalsRegistry for StaticDecimalsRegistry {
+    fn decimals(&self, coin_type: &str) -> Option<u8> {
+        self.decimals.get(&cache_key(coin_type)).copied()
+    }
+}
+
+pub(crate) fn cache_key(coin_type: &str) -> String {
+    let mut key = coin_type.to_string();
+    normalize_type(&mut key, AddressFormat::Long);
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usdc() -> CoinMetadata {
+        CoinMetadata {
+            decimals: Some(6),
+            symbol: "USDC".to_string(),
+            name: "USD Coin".to_string(),
+            icon_url: None,
+        }
+    }
+
+    #[test]
+    fn persists_entries_under_normalized_coin_types() {
+        let path = std::env::temp_dir().join(format!(
+            "tx_parse_coin_metadata_{}.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let cache = CoinMetadataCache::with_file(&path).expect("missing file should be empty");
+        cache.insert("0xdba3::usdc::USDC", usdc());
+        cache.save().expect("cache should save");
+
+        let reloaded = CoinMetadataCache::with_file(&path).expect("cache should load");
+        assert_eq!(
+            reloaded.get(
+                "0x000000000000000000000000000000000000000000000000000000000000dba3::usdc::USDC"
+            ),
+            Some(usdc())
+        );
+        assert_eq!(reloaded.get("0x2::sui::SUI"), None);
+
+        std::fs::remove_file(&path).expect("cache file should exist");
+    }
+
+    #[test]
+    fn saves_concurrently_without_leaving_temporary_files() {
+        let dir = std::env::temp_dir().join(format!(
+            "tx_parse_coin_metadata_concurrent_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir(&dir).expect("directory should be created");
+        let path = dir.join("coins.json");
+
+        let cache = CoinMetadataCache::with_file(&path).expect("missing file should be empty");
+        cache.insert("0xdba3::usdc::USDC", usdc());
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| cache.save().expect("cache should save"));
+            }
+        });
+
+        let files: Vec<_> = std::fs::read_dir(&dir)
+            .expect("directory should be readable")
+            .map(|entry| entry.expect("entry should be readable").file_name())
+            .collect();
+        assert_eq!(files, vec!["coins.json"]);
+        let reloaded = CoinMetadataCache::with_file(&path).expect("cache should load");
+        assert_eq!(reloaded.get("0xdba3::usdc::USDC"), Some(usdc()));
+
+        std::fs::remove_dir_all(&dir).expect("directory should be removed");
+    }
+
+    #[test]
+    fn looks_up_known_decimals_by_normalized_coin_type() {
+        let registry = StaticDecimalsRegistry::well_known().with("0xabc::lp::LP", 0);
+
+        assert_eq!(registry.decimals("0x02::sui::SUI"), Some(9));
+        assert_eq!(
+            registry.decimals(
+                "0x0000000000000000000000000000000000000000000000000000000000000abc::lp::LP"
+            ),
+            Some(0)
+        );
+        assert_eq!(registry.decimals("0xabc::lp::OTHER"), None);
+    }
+}
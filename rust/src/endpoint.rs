@@ -0,0 +1,219 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tokio::sync::SemaphorePermit;
+
+use crate::client::ClientError;
+use crate::rate_limit::{RateLimit, RateLimiter};
+
+/// How [`TxParseClient`](crate::TxParseClient) picks a fullnode when it is
+/// configured with several endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelectionStrategy {
+    /// Rotate through healthy endpoints in order.
+    #[default]
+    RoundRobin,
+    /// Prefer endpoints with a lower observed response time. Endpoints
+    /// without a measurement are tried first.
+    LatencyWeighted,
+}
+
+#[derive(Debug, Default)]
+struct EndpointHealth {
+    unhealthy_until: Option<Instant>,
+    latency: Option<Duration>,
+}
+
+#[derive(Debug)]
+struct Endpoint {
+    url: String,
+    health: Mutex<EndpointHealth>,
+    limiter: Option<RateLimiter>,
+}
+
+/// Endpoints shared by all clones of a client, along with their health.
+#[derive(Debug)]
+pub(crate) struct EndpointPool {
+    endpoints: Vec<Endpoint>,
+    strategy: SelectionStrategy,
+    cooldown: Duration,
+    next: AtomicUsize,
+}
+
+impl EndpointPool {
+    pub(crate) fn new(
+        urls: Vec<(String, Option<RateLimit>)>,
+        strategy: SelectionStrategy,
+        cooldown: Duration,
+    ) -> Self {
+        Self {
+            endpoints: urls
+                .into_iter()
+                .map(|(url, limit)| Endpoint {
+                    url,
+                    health: Mutex::default(),
+                    limiter: limit.map(RateLimiter::new),
+                })
+                .collect(),
+            strategy,
+            cooldown,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn url(&self, index: usize) -> &str {
+        &self.endpoints[index].url
+    }
+
+    /// Waits for the endpoint's rate limit, if it has one.
+    pub(crate) async fn acquire(&self, index: usize) -> Option<SemaphorePermit<'_>> {
+        match &self.endpoints[index].limiter {
+            Some(limiter) => limiter.acquire().await,
+            None => None,
+        }
+    }
+
+    /// Whether some endpoint has not been used for the current request yet.
+    pub(crate) fn has_untried(&self, tried: &[usize]) -> bool {
+        tried.len() < self.endpoints.len()
+    }
+
+    /// Picks the endpoint for the next attempt, preferring healthy endpoints
+    /// that have not been `tried` for the current request.
+    pub(crate) fn select(&self, tried: &[usize]) -> usize {
+        let now = Instant::now();
+        let untried: Vec<usize> = (0..self.endpoints.len())
+            .filter(|index| !tried.contains(index))
+            .collect();
+        let pool = if untried.is_empty() {
+            (0..self.endpoints.len()).collect()
+        } else {
+            untried
+        };
+        let healthy: Vec<usize> = pool
+            .iter()
+            .copied()
+            .filter(|&index| self.is_healthy(index, now))
+            .collect();
+        let candidates = if healthy.is_empty() { pool } else { healthy };
+
+        match self.strategy {
+            SelectionStrategy::RoundRobin => {
+                candidates[self.next.fetch_add(1, Ordering::Relaxed) % candidates.len()]
+            }
+            SelectionStrategy::LatencyWeighted => self.select_by_latency(&candidates),
+        }
+    }
+
+    pub(crate) fn record_success(&self, index: usize, elapsed: Duration) {
+        let mut health = self.health(index);
+        health.unhealthy_until = None;
+        // Exponentially weighted moving average so a single slow response
+        // does not dominate the selection.
+        health.latency = Some(match health.latency {
+            Some(previous) => previous.mul_f64(0.8) + elapsed.mul_f64(0.2),
+            None => elapsed,
+        });
+    }
+
+    pub(crate) fn record_failure(&self, index: usize) {
+        self.health(index).unhealthy_until = Some(Instant::now() + self.cooldown);
+    }
+
+    fn is_healthy(&self, index: usize, now: Instant) -> bool {
+        self.health(index)
+            .unhealthy_until
+            .is_none_or(|until| until <= now)
+    }
+
+    fn select_by_latency(&self, candidates: &[usize]) -> usize {
+        let latencies: Vec<(usize, Option<Duration>)> = candidates
+            .iter()
+            .map(|&index| (index, self.health(index).latency))
+            .collect();
+
+        if let Some(&(index, _)) = latencies.iter().find(|(_, latency)| latency.is_none()) {
+            return index;
+        }
+
+        let weights: Vec<(usize, f64)> = latencies
+            .into_iter()
+            .map(|(index, latency)| {
+                let seconds = latency.unwrap_or_default().as_secs_f64();
+                (index, 1.0 / seconds.max(1e-6))
+            })
+            .collect();
+        let total: f64 = weights.iter().map(|(_, weight)| weight).sum();
+        let mut target = fastrand::f64() * total;
+        for &(index, weight) in &weights {
+            if target < weight {
+                return index;
+            }
+            target -= weight;
+        }
+
+        weights[weights.len() - 1].0
+    }
+
+    fn health(&self, index: usize) -> std::sync::MutexGuard<'_, EndpointHealth> {
+        self.endpoints[index]
+            .health
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Whether `error` says something about the endpoint rather than the
+/// request: transport failures, timeouts, rate limiting and server errors.
+/// Only these mark an endpoint unhealthy.
+pub(crate) fn is_endpoint_failure(error: &ClientError) -> bool {
+    match error {
+        ClientError::Http(error) => !error.is_decode(),
+        ClientError::HttpStatus { status } => matches!(status, 408 | 429 | 500..=599),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(strategy: SelectionStrategy) -> EndpointPool {
+        EndpointPool::new(
+            ["a", "b", "c"]
+                .into_iter()
+                .map(|url| (url.to_string(), None))
+                .collect(),
+            strategy,
+            Duration::from_secs(60),
+        )
+    }
+
+    #[test]
+    fn rotates_and_skips_unhealthy_endpoints() {
+        let pool = pool(SelectionStrategy::RoundRobin);
+        let picks: Vec<usize> = (0..4).map(|_| pool.select(&[])).collect();
+        assert_eq!(picks, vec![0, 1, 2, 0]);
+
+        pool.record_failure(1);
+        assert!((0..6).all(|_| pool.select(&[]) != 1));
+        assert_eq!(pool.select(&[0, 2]), 1);
+
+        pool.record_success(1, Duration::from_millis(5));
+        assert!((0..6).any(|_| pool.select(&[]) == 1));
+    }
+
+    #[test]
+    fn prefers_untried_then_faster_endpoints() {
+        let pool = pool(SelectionStrategy::LatencyWeighted);
+        pool.record_success(0, Duration::from_millis(500));
+        pool.record_success(1, Duration::from_millis(5));
+        assert_eq!(pool.select(&[]), 2);
+
+        pool.record_success(2, Duration::from_millis(500));
+        let fast = (0..200).filter(|_| pool.select(&[]) == 1).count();
+        assert!(fast > 150, "fast endpoint picked {fast} times");
+        assert!(!pool.has_untried(&[0, 1, 2]));
+    }
+}
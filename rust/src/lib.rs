@@ -1,8 +1,40 @@
+pub mod address;
+mod aggregate;
+mod amount;
+mod balance_diff;
 mod client;
+mod coin_metadata;
+mod endpoint;
 mod parse;
+mod price;
+mod programmable;
+mod rate_limit;
+mod retry;
+pub mod type_tag;
 mod types;
 mod utils;
 
-pub use client::{ClientError, TxParseClient};
-pub use parse::{parse_transaction_value, ParseError};
-pub use types::{BalanceChange, DynamicFieldBalanceChange, GasCostSummary, ParseResult};
+pub use address::{AddressFormat, AddressParseError, ObjectId, SuiAddress};
+pub use aggregate::{BalanceSummary, CoinFlow, Imbalance, OwnerFlows};
+pub use amount::{AmountParseError, CoinAmount, RoundingMode};
+pub use client::{ClientError, TxParseClient, TxParseClientBuilder};
+pub use coin_metadata::{
+    CoinMetadata, CoinMetadataCache, DecimalsRegistry, StaticDecimalsRegistry,
+};
+pub use endpoint::SelectionStrategy;
+pub use num_bigint::{BigInt, BigUint};
+pub use parse::{
+    parse_transaction_value, parse_transaction_value_with_options, ParseError, ParseOptions,
+};
+pub use price::{PriceFuture, PriceSource, RpcPriceSource, StaticPriceSource, Valuation};
+pub use programmable::{
+    Argument, Command, MoveCall, ProgrammableTransaction, ResolvedArgument, TransactionInput,
+};
+pub use rate_limit::RateLimit;
+pub use retry::RetryPolicy;
+pub use type_tag::{StructTag, TypeTag, TypeTagParseError};
+pub use types::{
+    Authenticator, BalanceChange, DynamicFieldBalanceChange, DynamicFieldBalanceReport, ExecutionStatus,
+    GasCostSummary, GasData, MoveAbort, ObjectBalanceChange, ObjectChange, ObjectFailure,
+    ObjectFailureReason, Owner, ParseResult, TransactionEvent,
+};
@@ -1,8 +1,18 @@
+use regex::Regex;
 use serde_json::Value;
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::OnceLock;
 use thiserror::Error;
 
-use crate::types::{BalanceChange, ParseResult, TransactionBlockResponse};
-use crate::utils::get_actual_owner;
+use crate::address::{same_address, AddressFormat};
+use crate::programmable::{parse_programmable_transaction, Argument};
+use crate::types::{
+    BalanceChange, ExecutionStatus, GasCostSummary, GasData, MoveAbort, ObjectChange, Owner,
+    ParseResult, RawEvent, RawExecutionStatus, RawObjectChange, RawTransactionData,
+    TransactionBlockResponse, TransactionEvent,
+};
+use crate::utils::{get_actual_owner, is_sui_coin_type, SUI_COIN_TYPE};
 
 #[derive(Debug, Error)]
 pub enum ParseError {
@@ -10,40 +20,369 @@ pub enum ParseError {
     MissingGasUsage,
     #[error("transaction payload could not be deserialized: {0}")]
     InvalidPayload(#[from] serde_json::Error),
+    #[error("command {command} references an unknown argument {argument:?}")]
+    InvalidArgument { command: usize, argument: Argument },
+    #[error("field `{field}` is not a valid integer: {value:?}")]
+    InvalidNumber { field: &'static str, value: String },
 }
 
-pub(crate) fn parse_transaction(response: &TransactionBlockResponse) -> Result<ParseResult, ParseError> {
-    let gas_cost = response
+pub(crate) fn parse_number<T: FromStr>(field: &'static str, value: &str) -> Result<T, ParseError> {
+    value.parse().map_err(|_| ParseError::InvalidNumber {
+        field,
+        value: value.to_string(),
+    })
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Also report balance changes with the net gas fee removed from the gas
+    /// payer's SUI entry, in `ParseResult::transfer_balance_changes`.
+    pub separate_gas: bool,
+    /// Request `showObjectChanges` and report every created, mutated,
+    /// transferred, deleted, wrapped, unwrapped and published object in
+    /// `ParseResult::object_changes`.
+    pub object_changes: bool,
+    /// Rewrite addresses, object IDs and coin types in the result in the
+    /// given format. Values are left as the fullnode returned them when unset.
+    pub address_format: Option<AddressFormat>,
+    /// Attach [`CoinMetadata`](crate::CoinMetadata) to each balance change.
+    /// Only used by [`TxParseClient`](crate::TxParseClient), which looks it
+    /// up in its coin metadata cache; offline parsing leaves it unset.
+    pub coin_metadata: bool,
+}
+
+pub(crate) fn parse_transaction(
+    response: &TransactionBlockResponse,
+) -> Result<ParseResult, ParseError> {
+    parse_transaction_with_options(response, &ParseOptions::default())
+}
+
+pub(crate) fn parse_transaction_with_options(
+    response: &TransactionBlockResponse,
+    options: &ParseOptions,
+) -> Result<ParseResult, ParseError> {
+    let gas_used = response
         .effects
         .as_ref()
-        .and_then(|effects| effects.gas_used.clone())
+        .and_then(|effects| effects.gas_used.as_ref())
         .ok_or(ParseError::MissingGasUsage)?;
 
+    let gas_cost = GasCostSummary {
+        computation_cost: parse_number("computationCost", &gas_used.computation_cost)?,
+        storage_cost: parse_number("storageCost", &gas_used.storage_cost)?,
+        storage_rebate: parse_number("storageRebate", &gas_used.storage_rebate)?,
+        non_refundable_storage_fee: parse_number(
+            "nonRefundableStorageFee",
+            &gas_used.non_refundable_storage_fee,
+        )?,
+    };
+
     let balance_changes = response
         .balance_changes
         .iter()
-        .map(|change| BalanceChange {
-            coin_type: change.coin_type.clone(),
-            amount: change.amount.clone(),
-            owner: get_actual_owner(change.owner.as_ref()).unwrap_or_default(),
+        .map(|change| {
+            Ok(BalanceChange {
+                coin_type: change.coin_type.clone(),
+                amount: parse_number("amount", &change.amount)?,
+                owner: get_actual_owner(change.owner.as_ref()).unwrap_or_default(),
+                owner_details: change.owner.as_ref().map(Owner::from),
+                coin_metadata: None,
+                valuation: None,
+            })
         })
-        .collect();
+        .collect::<Result<Vec<_>, ParseError>>()?;
+
+    let events = response.events.iter().map(parse_event).collect();
+
+    let transaction = match &response.transaction {
+        Some(block) => parse_programmable_transaction(&block.data.transaction)?,
+        None => None,
+    };
+
+    let sender = response
+        .transaction
+        .as_ref()
+        .map(|block| block.data.sender.clone());
+
+    let gas_data = response
+        .transaction
+        .as_ref()
+        .map(|block| parse_gas_data(&block.data))
+        .transpose()?;
+
+    let net_gas_fee = gas_cost.net_fee();
+
+    let transfer_balance_changes = match (&gas_data, options.separate_gas) {
+        (Some(gas_data), true) => Some(remove_gas_fee(
+            &balance_changes,
+            &gas_data.owner,
+            net_gas_fee,
+        )),
+        _ => None,
+    };
+
+    let status = response
+        .effects
+        .as_ref()
+        .and_then(|effects| effects.status.as_ref())
+        .map(parse_execution_status);
+
+    let object_changes = match (&response.object_changes, options.object_changes) {
+        (Some(changes), true) => Some(parse_object_changes(response, changes)?),
+        _ => None,
+    };
 
-    Ok(ParseResult {
+    let mut result = ParseResult {
         balance_changes,
+        transfer_balance_changes,
         gas_cost,
+        events,
+        transaction,
+        status,
+        sender,
+        gas_data,
+        net_gas_fee,
+        object_changes,
+        timestamp_ms: response.timestamp_ms.clone(),
+        gas_fee_valuation: None,
+    };
+
+    if let Some(format) = options.address_format {
+        result.normalize_addresses(format);
+    }
+
+    Ok(result)
+}
+
+fn parse_object_changes(
+    response: &TransactionBlockResponse,
+    changes: &[RawObjectChange],
+) -> Result<Vec<ObjectChange>, ParseError> {
+    // objectChanges reports unwrapped objects as created or mutated; the
+    // effects list them separately.
+    let unwrapped: HashSet<&str> = response
+        .effects
+        .iter()
+        .flat_map(|effects| &effects.unwrapped)
+        .map(|unwrapped| unwrapped.reference.object_id.as_str())
+        .collect();
+
+    let mut parsed = Vec::with_capacity(changes.len());
+    for change in changes {
+        let change = match change {
+            RawObjectChange::Created {
+                object_id,
+                object_type,
+                version,
+                digest,
+                owner,
+            }
+            | RawObjectChange::Mutated {
+                object_id,
+                object_type,
+                version,
+                digest,
+                owner,
+                ..
+            } if unwrapped.contains(object_id.as_str()) => ObjectChange::Unwrapped {
+                object_id: object_id.clone(),
+                object_type: object_type.clone(),
+                owner: owner.as_ref().map(Owner::from),
+                version: parse_number("version", version)?,
+                digest: digest.clone(),
+            },
+            RawObjectChange::Created {
+                object_id,
+                object_type,
+                version,
+                digest,
+                owner,
+            } => ObjectChange::Created {
+                object_id: object_id.clone(),
+                object_type: object_type.clone(),
+                owner: owner.as_ref().map(Owner::from),
+                version: parse_number("version", version)?,
+                digest: digest.clone(),
+            },
+            RawObjectChange::Mutated {
+                object_id,
+                object_type,
+                version,
+                previous_version,
+                digest,
+                owner,
+            } => ObjectChange::Mutated {
+                object_id: object_id.clone(),
+                object_type: object_type.clone(),
+                owner: owner.as_ref().map(Owner::from),
+                version: parse_number("version", version)?,
+                previous_version: parse_number("previousVersion", previous_version)?,
+                digest: digest.clone(),
+            },
+            RawObjectChange::Transferred {
+                object_id,
+                object_type,
+                version,
+                digest,
+                recipient,
+            } => ObjectChange::Transferred {
+                object_id: object_id.clone(),
+                object_type: object_type.clone(),
+                recipient: recipient.as_ref().map(Owner::from),
+                version: parse_number("version", version)?,
+                digest: digest.clone(),
+            },
+            RawObjectChange::Deleted {
+                object_id,
+                object_type,
+                version,
+            } => ObjectChange::Deleted {
+                object_id: object_id.clone(),
+                object_type: object_type.clone(),
+                version: parse_number("version", version)?,
+            },
+            RawObjectChange::Wrapped {
+                object_id,
+                object_type,
+                version,
+            } => ObjectChange::Wrapped {
+                object_id: object_id.clone(),
+                object_type: object_type.clone(),
+                version: parse_number("version", version)?,
+            },
+            RawObjectChange::Published {
+                package_id,
+                version,
+                digest,
+                modules,
+            } => ObjectChange::Published {
+                package_id: package_id.clone(),
+                version: parse_number("version", version)?,
+                digest: digest.clone(),
+                modules: modules.clone(),
+            },
+            RawObjectChange::Other => continue,
+        };
+        parsed.push(change);
+    }
+
+    Ok(parsed)
+}
+
+/// Adds the net gas fee back to the gas payer's SUI delta so that the
+/// remaining amount only reflects transfers. Entries that end up at zero are
+/// dropped, mirroring how fullnodes omit unchanged balances.
+fn remove_gas_fee(
+    balance_changes: &[BalanceChange],
+    gas_owner: &str,
+    net_gas_fee: i128,
+) -> Vec<BalanceChange> {
+    let mut adjusted = balance_changes.to_vec();
+    let position = adjusted.iter().position(|change| {
+        same_address(&change.owner, gas_owner) && is_sui_coin_type(&change.coin_type)
+    });
+
+    match position {
+        Some(index) => {
+            adjusted[index].amount += net_gas_fee;
+            if adjusted[index].amount == 0 {
+                adjusted.remove(index);
+            }
+        }
+        None if net_gas_fee != 0 => adjusted.push(BalanceChange {
+            coin_type: SUI_COIN_TYPE.to_string(),
+            amount: net_gas_fee,
+            owner: gas_owner.to_string(),
+            owner_details: Some(Owner::Address {
+                address: gas_owner.to_string(),
+            }),
+            coin_metadata: None,
+            valuation: None,
+        }),
+        None => {}
+    }
+
+    adjusted
+}
+
+fn parse_gas_data(data: &RawTransactionData) -> Result<GasData, ParseError> {
+    Ok(GasData {
+        owner: data.gas_data.owner.clone(),
+        price: parse_number("gasData.price", &data.gas_data.price)?,
+        budget: parse_number("gasData.budget", &data.gas_data.budget)?,
+        sponsored: data.gas_data.owner != data.sender,
+    })
+}
+
+fn parse_execution_status(status: &RawExecutionStatus) -> ExecutionStatus {
+    ExecutionStatus {
+        success: status.status == "success",
+        error: status.error.clone(),
+        move_abort: status.error.as_deref().and_then(parse_move_abort),
+    }
+}
+
+// Matches the Debug rendering of `ExecutionFailureStatus::MoveAbort` that
+// fullnodes put into `effects.status.error`.
+fn move_abort_regex() -> &'static Regex {
+    static MOVE_ABORT: OnceLock<Regex> = OnceLock::new();
+    MOVE_ABORT.get_or_init(|| {
+        Regex::new(concat!(
+            r"MoveAbort\(MoveLocation \{ module: ModuleId \{ address: (?:0x)?([0-9a-fA-F]+), ",
+            r#"name: Identifier\("([^"]+)"\) \}, function: \d+, instruction: \d+, "#,
+            r#"function_name: (?:Some\("([^"]+)"\)|None) \}, (\d+)\)"#,
+            r"(?: in command (\d+))?",
+        ))
+        .expect("move abort pattern is valid")
     })
 }
 
+fn parse_move_abort(error: &str) -> Option<MoveAbort> {
+    let captures = move_abort_regex().captures(error)?;
+
+    Some(MoveAbort {
+        package: format!("0x{}", &captures[1]),
+        module: captures[2].to_string(),
+        function: captures.get(3).map(|name| name.as_str().to_string()),
+        abort_code: captures[4].parse().ok()?,
+        command_index: captures
+            .get(5)
+            .and_then(|index| index.as_str().parse().ok()),
+    })
+}
+
+fn parse_event(event: &RawEvent) -> TransactionEvent {
+    TransactionEvent {
+        package_id: event.package_id.clone(),
+        module: event.transaction_module.clone(),
+        sender: event.sender.clone(),
+        event_type: event.type_.clone(),
+        parsed_json: event.parsed_json.clone(),
+        bcs: event.bcs.clone(),
+        bcs_encoding: event.bcs_encoding.clone(),
+        event_seq: event.id.event_seq.clone(),
+        timestamp_ms: event.timestamp_ms.clone(),
+    }
+}
+
 pub fn parse_transaction_value(value: &Value) -> Result<ParseResult, ParseError> {
     let response: TransactionBlockResponse = serde_json::from_value(value.clone())?;
     parse_transaction(&response)
 }
 
+pub fn parse_transaction_value_with_options(
+    value: &Value,
+    options: &ParseOptions,
+) -> Result<ParseResult, ParseError> {
+    let response: TransactionBlockResponse = serde_json::from_value(value.clone())?;
+    parse_transaction_with_options(&response, options)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::TransactionBlockResponse;
+    use crate::programmable::{ResolvedArgument, TransactionInput};
+    use crate::types::{Authenticator, TransactionBlockResponse};
 
     fn load_fixture() -> TransactionBlockResponse {
         let raw = include_str!("../../fixtures/transaction_block.json");
@@ -60,7 +399,416 @@ mod tests {
         assert_eq!(parsed.balance_changes[2].owner, "Shared-42");
         assert_eq!(parsed.balance_changes[3].owner, "ConsensusV2-77");
         assert_eq!(parsed.balance_changes[4].owner, "Immutable");
-        assert_eq!(parsed.gas_cost.computation_cost, "100");
+        assert_eq!(parsed.balance_changes[1].amount, -750);
+        assert_eq!(parsed.gas_cost.computation_cost, 100);
+        assert_eq!(parsed.gas_cost.storage_rebate, 50);
+    }
+
+    #[test]
+    fn parses_events_and_filters_by_type() {
+        let response = load_fixture();
+        let parsed = parse_transaction(&response).expect("parse should succeed");
+
+        assert_eq!(parsed.events.len(), 2);
+        let swap = &parsed.events[0];
+        assert_eq!(swap.package_id, "0xabc");
+        assert_eq!(swap.module, "pool");
+        assert_eq!(swap.sender, "0x6f4d3a");
+        assert_eq!(swap.event_seq, "0");
+        assert_eq!(swap.bcs_encoding.as_deref(), Some("base64"));
+        assert_eq!(swap.parsed_json["amount_out"], "750");
+
+        let swaps: Vec<_> = parsed.events_of_type("0xabc::pool::SwapEvent").collect();
+        assert_eq!(swaps.len(), 1);
+        assert_eq!(
+            parsed
+                .events_of_type("0xabc::pool::SwapEvent<0x2::sui::SUI, 0x2::other::COIN>")
+                .count(),
+            1
+        );
+        assert_eq!(parsed.events_of_type("0xabc::pool::Swap").count(), 0);
+        assert_eq!(parsed.events_from_module("0xabc", "pool").count(), 2);
+    }
+
+    #[test]
+    fn parses_programmable_transaction_commands() {
+        let response = load_fixture();
+        let parsed = parse_transaction(&response).expect("parse should succeed");
+        let transaction = parsed.transaction.expect("fixture is programmable");
+
+        assert_eq!(transaction.inputs.len(), 3);
+        assert_eq!(transaction.commands.len(), 3);
+
+        let calls: Vec<_> = transaction.move_calls().collect();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].function, "swap");
+        assert_eq!(
+            calls[0].type_arguments,
+            vec!["0x2::sui::SUI", "0x2::other::COIN"]
+        );
+        assert!(matches!(
+            transaction.resolve(calls[0].arguments[0]),
+            Some(ResolvedArgument::Input {
+                input: TransactionInput::SharedObject { mutable: true, .. },
+                ..
+            })
+        ));
+        assert_eq!(transaction.dependencies(1), vec![0]);
+        assert_eq!(transaction.dependencies(2), vec![1]);
+    }
+
+    #[test]
+    fn reports_successful_execution() {
+        let parsed = parse_transaction(&load_fixture()).expect("parse should succeed");
+
+        assert!(parsed.is_success());
+        assert_eq!(parsed.status.and_then(|status| status.error), None);
+    }
+
+    #[test]
+    fn parses_move_abort_from_failure_status() {
+        let raw = include_str!("../../fixtures/transaction_block.json");
+        let mut value: Value = serde_json::from_str(raw).expect("fixture should parse");
+        value["effects"]["status"] = serde_json::json!({
+            "status": "failure",
+            "error": "MoveAbort(MoveLocation { module: ModuleId { address: 000000000000000000000000000000000000000000000000000000000000abc0, name: Identifier(\"pool\") }, function: 3, instruction: 42, function_name: Some(\"swap\") }, 1004) in command 1",
+        });
+
+        let parsed = parse_transaction_value(&value).expect("value parsing should succeed");
+        assert!(!parsed.is_success());
+
+        let status = parsed.status.expect("status should be present");
+        assert!(status
+            .error
+            .is_some_and(|error| error.starts_with("MoveAbort")));
+        assert_eq!(
+            status.move_abort,
+            Some(MoveAbort {
+                package: "0x000000000000000000000000000000000000000000000000000000000000abc0"
+                    .to_string(),
+                module: "pool".to_string(),
+                function: Some("swap".to_string()),
+                abort_code: 1004,
+                command_index: Some(1),
+            })
+        );
+    }
+
+    #[test]
+    fn keeps_non_abort_failures_unstructured() {
+        let status = parse_execution_status(&RawExecutionStatus {
+            status: "failure".to_string(),
+            error: Some("InsufficientCoinBalance in command 0".to_string()),
+        });
+
+        assert!(!status.success);
+        assert_eq!(status.move_abort, None);
+    }
+
+    #[test]
+    fn computes_net_gas_fee_and_gas_payer() {
+        let parsed = parse_transaction(&load_fixture()).expect("parse should succeed");
+
+        assert_eq!(parsed.net_gas_fee, 250);
+        assert_eq!(parsed.sender.as_deref(), Some("0x6f4d3a"));
+        assert_eq!(
+            parsed.gas_data,
+            Some(GasData {
+                owner: "0x6f4d3a".to_string(),
+                price: 750,
+                budget: 5_000_000,
+                sponsored: false,
+            })
+        );
+    }
+
+    #[test]
+    fn flags_sponsored_transactions_and_negative_fees() {
+        let raw = include_str!("../../fixtures/transaction_block.json");
+        let mut value: Value = serde_json::from_str(raw).expect("fixture should parse");
+        value["transaction"]["data"]["gasData"]["owner"] = "0x5905".into();
+        value["effects"]["gasUsed"]["storageRebate"] = "1000".into();
+
+        let parsed = parse_transaction_value(&value).expect("value parsing should succeed");
+        let gas_data = parsed.gas_data.expect("gas data should be present");
+
+        assert!(gas_data.sponsored);
+        assert_eq!(gas_data.owner, "0x5905");
+        assert_eq!(parsed.net_gas_fee, -700);
+    }
+
+    #[test]
+    fn recomputes_net_gas_fee_missing_from_stored_results() {
+        let parsed = parse_transaction(&load_fixture()).expect("parse should succeed");
+        let mut stored = serde_json::to_value(&parsed).expect("result should serialize");
+        assert_eq!(stored["netGasFee"], "250");
+        stored
+            .as_object_mut()
+            .expect("result should be an object")
+            .remove("netGasFee");
+
+        let reloaded: ParseResult =
+            serde_json::from_value(stored).expect("result should deserialize");
+        assert_eq!(reloaded, parsed);
+    }
+
+    #[test]
+    fn separates_gas_from_transfers_when_requested() {
+        let response = load_fixture();
+        let options = ParseOptions {
+            separate_gas: true,
+            ..ParseOptions::default()
+        };
+
+        let parsed =
+            parse_transaction_with_options(&response, &options).expect("parse should succeed");
+        let transfers = parsed
+            .transfer_balance_changes
+            .expect("transfer view should be present");
+
+        assert_eq!(parsed.balance_changes[0].amount, 1000);
+        assert_eq!(transfers.len(), parsed.balance_changes.len());
+        assert_eq!(transfers[0].owner, "0x6f4d3a");
+        assert_eq!(transfers[0].amount, 1250);
+        assert_eq!(transfers[1..], parsed.balance_changes[1..]);
+
+        let default = parse_transaction(&response).expect("parse should succeed");
+        assert_eq!(default.transfer_balance_changes, None);
+    }
+
+    #[test]
+    fn drops_gas_payer_entry_when_it_only_paid_gas() {
+        let changes = vec![BalanceChange {
+            coin_type: "0x2::sui::SUI".to_string(),
+            amount: -250,
+            owner: "0x6f4d3a".to_string(),
+            owner_details: None,
+            coin_metadata: None,
+            valuation: None,
+        }];
+
+        assert!(remove_gas_fee(&changes, "0x6f4d3a", 250).is_empty());
+
+        let sponsored = remove_gas_fee(&changes, "0x5905", 250);
+        assert_eq!(sponsored.len(), 2);
+        assert_eq!(sponsored[1].owner, "0x5905");
+        assert_eq!(sponsored[1].amount, 250);
+    }
+
+    #[test]
+    fn reports_object_changes_when_requested() {
+        let response = load_fixture();
+        let options = ParseOptions {
+            object_changes: true,
+            ..ParseOptions::default()
+        };
+
+        let parsed =
+            parse_transaction_with_options(&response, &options).expect("parse should succeed");
+        let changes = parsed
+            .object_changes
+            .expect("object changes should be present");
+
+        assert_eq!(changes.len(), 7);
+        assert_eq!(
+            changes[0],
+            ObjectChange::Mutated {
+                object_id: "0xbeef".to_string(),
+                object_type: "0xabc::pool::Pool<0x2::sui::SUI, 0x2::other::COIN>".to_string(),
+                owner: Some(Owner::Shared {
+                    initial_shared_version: 42,
+                    mutable: None,
+                }),
+                version: 12,
+                previous_version: 11,
+                digest: "POOLDIGEST".to_string(),
+            }
+        );
+        assert!(matches!(changes[1], ObjectChange::Created { .. }));
+        assert!(matches!(
+            &changes[2],
+            ObjectChange::Unwrapped { object_id, .. } if object_id == "0xd00d"
+        ));
+        assert!(matches!(
+            &changes[3],
+            ObjectChange::Transferred { recipient: Some(Owner::Address { address }), .. }
+                if address == "0x5905"
+        ));
+        assert!(matches!(
+            changes[4],
+            ObjectChange::Deleted { version: 12, .. }
+        ));
+        assert!(matches!(changes[5], ObjectChange::Wrapped { .. }));
+        assert_eq!(
+            changes[6],
+            ObjectChange::Published {
+                package_id: "0xf1".to_string(),
+                version: 1,
+                digest: "PKGDIGEST".to_string(),
+                modules: vec!["pool".to_string(), "nft".to_string()],
+            }
+        );
+        assert_eq!(changes[6].object_type(), None);
+
+        let default = parse_transaction(&response).expect("parse should succeed");
+        assert_eq!(default.object_changes, None);
+    }
+
+    #[test]
+    fn normalizes_addresses_when_requested() {
+        let response = load_fixture();
+        let options = ParseOptions {
+            separate_gas: true,
+            object_changes: true,
+            address_format: Some(AddressFormat::Long),
+            ..ParseOptions::default()
+        };
+
+        let parsed =
+            parse_transaction_with_options(&response, &options).expect("parse should succeed");
+        let owner = "0x00000000000000000000000000000000000000000000000000000000006f4d3a";
+
+        assert_eq!(parsed.balance_changes[0].owner, owner);
+        assert_eq!(
+            parsed.balance_changes[0].coin_type,
+            "0x0000000000000000000000000000000000000000000000000000000000000002::sui::SUI"
+        );
+        assert_eq!(
+            parsed.balance_changes[0].owner_details,
+            Some(Owner::Address {
+                address: owner.to_string()
+            })
+        );
+        assert_eq!(parsed.balance_changes[2].owner, "Shared-42");
+        assert_eq!(parsed.sender.as_deref(), Some(owner));
+        assert_eq!(parsed.gas_data.map(|gas| gas.owner).as_deref(), Some(owner));
+        assert_eq!(parsed.transfer_balance_changes.unwrap()[0].amount, 1250);
+        assert_eq!(
+            parsed.object_changes.unwrap()[3].object_id(),
+            "0x0000000000000000000000000000000000000000000000000000000000000a47"
+        );
+
+        let mut short = parsed_with_long_addresses();
+        short.normalize_addresses(AddressFormat::Short);
+        assert_eq!(short.balance_changes[0].owner, "0x6f4d3a");
+        assert_eq!(short.balance_changes[0].coin_type, "0x2::sui::SUI");
+    }
+
+    fn parsed_with_long_addresses() -> ParseResult {
+        let options = ParseOptions {
+            address_format: Some(AddressFormat::Long),
+            ..ParseOptions::default()
+        };
+        parse_transaction_with_options(&load_fixture(), &options).expect("parse should succeed")
+    }
+
+    #[test]
+    fn rejects_malformed_amounts() {
+        let mut response = load_fixture();
+        response.balance_changes[0].amount = "12abc".to_string();
+
+        let err = parse_transaction(&response).expect_err("should fail on bad amount");
+        assert!(matches!(
+            err,
+            ParseError::InvalidNumber { field: "amount", ref value } if value == "12abc"
+        ));
+    }
+
+    #[test]
+    fn serializes_amounts_as_strings() {
+        let parsed = parse_transaction(&load_fixture()).expect("parse should succeed");
+        let value = serde_json::to_value(&parsed).expect("result should serialize");
+
+        assert_eq!(value["balanceChanges"][1]["amount"], "-750");
+        assert_eq!(value["gasCost"]["computationCost"], "100");
+
+        let round_trip: ParseResult =
+            serde_json::from_value(value).expect("result should deserialize");
+        assert_eq!(round_trip, parsed);
+    }
+
+    #[test]
+    fn exposes_structured_owners() {
+        let parsed = parse_transaction(&load_fixture()).expect("parse should succeed");
+        let owners: Vec<_> = parsed
+            .balance_changes
+            .iter()
+            .map(|change| {
+                change
+                    .owner_details
+                    .clone()
+                    .expect("owner should be present")
+            })
+            .collect();
+
+        assert_eq!(
+            owners,
+            vec![
+                Owner::Address {
+                    address: "0x6f4d3a".to_string()
+                },
+                Owner::Object {
+                    object_id: "0x123456".to_string()
+                },
+                Owner::Shared {
+                    initial_shared_version: 42,
+                    mutable: Some(true)
+                },
+                Owner::ConsensusV2 {
+                    start_version: 77,
+                    authenticator: None
+                },
+                Owner::Immutable,
+            ]
+        );
+        for change in &parsed.balance_changes {
+            assert_eq!(
+                change.owner,
+                change.owner_details.as_ref().unwrap().to_string()
+            );
+        }
+
+        let value = serde_json::to_value(&owners[2]).expect("owner should serialize");
+        assert_eq!(
+            value,
+            serde_json::json!({ "kind": "shared", "initialSharedVersion": "42", "mutable": true })
+        );
+    }
+
+    #[test]
+    fn keeps_consensus_authenticators() {
+        let raw = include_str!("../../fixtures/transaction_block.json");
+        let mut value: Value = serde_json::from_str(raw).expect("fixture should parse");
+        value["balanceChanges"][3]["owner"]["ConsensusV2"]["authenticator"] =
+            serde_json::json!({ "SingleOwner": "0x5905" });
+        value["balanceChanges"][4]["owner"] = serde_json::json!({
+            "ConsensusV2": {
+                "start_version": "78",
+                "authenticator": { "MultiOwner": ["0x5905", "0x6f4d3a"] },
+            },
+        });
+
+        let parsed = parse_transaction_value(&value).expect("value parsing should succeed");
+        let authenticators: Vec<_> = parsed.balance_changes[3..]
+            .iter()
+            .map(|change| match &change.owner_details {
+                Some(Owner::ConsensusV2 { authenticator, .. }) => authenticator.clone(),
+                other => panic!("unexpected owner {other:?}"),
+            })
+            .collect();
+
+        assert_eq!(
+            authenticators,
+            vec![
+                Some(Authenticator::SingleOwner {
+                    address: "0x5905".to_string()
+                }),
+                Some(Authenticator::Unknown {
+                    value: serde_json::json!({ "MultiOwner": ["0x5905", "0x6f4d3a"] })
+                }),
+            ]
+        );
+        assert_eq!(parsed.balance_changes[4].owner, "ConsensusV2-78");
     }
 
     #[test]
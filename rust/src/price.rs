@@ -0,0 +1,441 @@
+use num_bigint::BigInt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::amount::CoinAmount;
+use crate::client::{ClientError, TxParseClient};
+use crate::coin_metadata::cache_key;
+use crate::types::ParseResult;
+use crate::utils::SUI_COIN_TYPE;
+
+/// Future returned by [`PriceSource::usd_price`].
+pub type PriceFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Option<CoinAmount>, ClientError>> + Send + 'a>>;
+
+/// Source of USD prices for [`TxParseClient::attach_valuations`].
+///
+/// Object safe, so sources can be chosen at runtime and shared as
+/// `Arc<dyn PriceSource>`.
+pub trait PriceSource: fmt::Debug + Send + Sync {
+    /// USD price of one whole `coin_type` at `timestamp_ms`, or `None` when
+    /// the source has no price for it.
+    fn usd_price<'a>(&'a self, coin_type: &'a str, timestamp_ms: Option<u64>) -> PriceFuture<'a>;
+}
+
+/// USD value of an amount of coins.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Valuation {
+    /// USD price of one whole coin.
+    pub usd_price: CoinAmount,
+    /// Exact value of the amount at `usd_price`; round it for display.
+    pub usd_value: CoinAmount,
+}
+
+impl Valuation {
+    pub fn new(amount: &CoinAmount, usd_price: CoinAmount) -> Self {
+        Self {
+            usd_value: amount.multiply(&usd_price),
+            usd_price,
+        }
+    }
+}
+
+/// Fixed prices keyed by coin type, ignoring the timestamp.
+#[derive(Debug, Clone, Default)]
+pub struct StaticPriceSource {
+    prices: HashMap<String, CoinAmount>,
+}
+
+impl StaticPriceSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, coin_type: &str, usd_price: CoinAmount) -> Self {
+        self.prices.insert(cache_key(coin_type), usd_price);
+        self
+    }
+}
+
+impl PriceSource for StaticPriceSource {
+    fn usd_price<'a>(&'a self, coin_type: &'a str, _timestamp_ms: Option<u64>) -> PriceFuture<'a> {
+        let price = self.prices.get(&cache_key(coin_type)).cloned();
+        Box::pin(async move { Ok(price) })
+    }
+}
+
+/// Prices from a JSON-RPC oracle, queried through a [`TxParseClient`] so
+/// that its retries, failover and rate limits apply.
+///
+/// `method` is called with `[coinType, timestampMs]` and must return the
+/// USD price of one whole coin as a decimal string or number, or `null`
+/// when it has none.
+#[derive(Debug, Clone)]
+pub struct RpcPriceSource {
+    client: TxParseClient,
+    method: String,
+}
+
+impl RpcPriceSource {
+    pub fn new(client: TxParseClient, method: impl Into<String>) -> Self {
+        Self {
+            client,
+            method: method.into(),
+        }
+    }
+}
+
+impl PriceSource for RpcPriceSource {
+    fn usd_price<'a>(&'a self, coin_type: &'a str, timestamp_ms: Option<u64>) -> PriceFuture<'a> {
+        Box::pin(self.fetch_price(coin_type, timestamp_ms))
+    }
+}
+
+impl RpcPriceSource {
+    async fn fetch_price(
+        &self,
+        coin_type: &str,
+        timestamp_ms: Option<u64>,
+    ) -> Result<Option<CoinAmount>, ClientError> {
+        let price: Value = match self
+            .client
+            .call(&self.method, json!([coin_type, timestamp_ms]))
+            .await
+        {
+            Ok(price) => price,
+            Err(ClientError::MissingResult) => return Ok(None),
+            Err(error) => return Err(error),
+        };
+
+        let parsed = match &price {
+            Value::String(price) => parse_price(price),
+            Value::Number(price) => parse_price(&price.to_string()),
+            _ => None,
+        };
+        parsed.map(Some).ok_or_else(|| ClientError::InvalidPrice {
+            coin_type: coin_type.to_string(),
+            value: price.to_string(),
+        })
+    }
+}
+
+/// Parses a decimal price, also in the exponent form serde_json renders
+/// small and large numbers in, e.g. `1.2e-7`.
+fn parse_price(price: &str) -> Option<CoinAmount> {
+    let (mantissa, exponent) = match price.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i32>().ok()?),
+        None => (price, 0),
+    };
+    let mantissa: CoinAmount = mantissa.parse().ok()?;
+    let decimals = i32::from(mantissa.decimals()).checked_sub(exponent)?;
+    if decimals >= 0 {
+        Some(CoinAmount::new(
+            mantissa.raw().clone(),
+            u8::try_from(decimals).ok()?,
+        ))
+    } else {
+        let scale = BigInt::from(10u8).pow(decimals.unsigned_abs());
+        Some(CoinAmount::new(mantissa.raw() * scale, 0))
+    }
+}
+
+impl TxParseClient {
+    /// Attaches a USD valuation to every balance change and to the net gas
+    /// fee, priced at the transaction's timestamp. Each coin type is priced
+    /// once, whichever form its address is written in. Coins without a
+    /// price, or whose decimals are unknown, are left without a valuation;
+    /// decimals are only looked up for priced coins. Fails when `prices`
+    /// fails or the coin metadata cannot be fetched.
+    pub async fn attach_valuations(
+        &self,
+        parsed: &mut ParseResult,
+        prices: &dyn PriceSource,
+    ) -> Result<(), ClientError> {
+        let timestamp_ms = parsed
+            .timestamp_ms
+            .as_deref()
+            .and_then(|timestamp| timestamp.parse().ok());
+
+        let mut coin_types: Vec<&str> = parsed
+            .balance_changes
+            .iter()
+            .chain(parsed.transfer_balance_changes.iter().flatten())
+            .map(|change| change.coin_type.as_str())
+            .collect();
+        coin_types.push(SUI_COIN_TYPE);
+        let mut quotes: HashMap<String, Option<(CoinAmount, u8)>> = HashMap::new();
+        for coin_type in coin_types {
+            let key = cache_key(coin_type);
+            if quotes.contains_key(&key) {
+                continue;
+            }
+            let quote = match prices.usd_price(coin_type, timestamp_ms).await? {
+                // An amount cannot be valued without its decimals, but the
+                // other changes still can.
+                Some(price) => match self.get_coin_decimals(coin_type).await {
+                    Ok(decimals) => Some((price, decimals)),
+                    Err(ClientError::UnknownDecimals { .. }) => None,
+                    Err(error) => return Err(error),
+                },
+                None => None,
+            };
+            quotes.insert(key, quote);
+        }
+
+        let value = |coin_type: &str, amount: i128| {
+            quotes
+                .get(&cache_key(coin_type))
+                .cloned()
+                .flatten()
+                .map(|(price, decimals)| Valuation::new(&CoinAmount::new(amount, decimals), price))
+        };
+        for change in parsed
+            .balance_changes
+            .iter_mut()
+            .chain(parsed.transfer_balance_changes.iter_mut().flatten())
+        {
+            change.valuation = value(&change.coin_type, change.amount);
+        }
+        parsed.gas_fee_valuation = value(SUI_COIN_TYPE, parsed.net_gas_fee);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coin_metadata::{CoinMetadata, CoinMetadataCache};
+    use crate::parse::parse_transaction_value;
+    use httpmock::Method::POST;
+    use httpmock::MockServer;
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    fn ensure_socket_permission() -> bool {
+        TcpListener::bind("127.0.0.1:0").is_ok()
+    }
+
+    fn load_fixture() -> ParseResult {
+        let raw = include_str!("../../fixtures/transaction_block.json");
+        let value: Value = serde_json::from_str(raw).expect("fixture should parse");
+        parse_transaction_value(&value).expect("fixture should be valid")
+    }
+
+    /// A client that knows the decimals of SUI and `0x2::other::COIN`
+    /// without asking a node.
+    fn client_with_metadata(rpc_url: String) -> TxParseClient {
+        let cache = CoinMetadataCache::new();
+        for (coin_type, decimals) in [("0x2::sui::SUI", 9), ("0x2::other::COIN", 2)] {
+            cache.insert(
+                coin_type,
+                CoinMetadata {
+                    decimals: Some(decimals),
+                    symbol: String::new(),
+                    name: String::new(),
+                    icon_url: None,
+                },
+            );
+        }
+        TxParseClient::builder(rpc_url)
+            .coin_metadata_cache(Arc::new(cache))
+            .build()
+    }
+
+    fn usd(price: &str) -> CoinAmount {
+        price.parse().expect("price should parse")
+    }
+
+    /// Static prices that record which coin types were asked for.
+    #[derive(Debug)]
+    struct RecordingPrices {
+        prices: StaticPriceSource,
+        requests: Mutex<Vec<String>>,
+    }
+
+    impl PriceSource for RecordingPrices {
+        fn usd_price<'a>(
+            &'a self,
+            coin_type: &'a str,
+            timestamp_ms: Option<u64>,
+        ) -> PriceFuture<'a> {
+            self.requests
+                .lock()
+                .expect("lock should not be poisoned")
+                .push(coin_type.to_string());
+            self.prices.usd_price(coin_type, timestamp_ms)
+        }
+    }
+
+    #[tokio::test]
+    async fn values_balance_changes_and_gas_with_static_prices() {
+        let client = client_with_metadata("http://127.0.0.1:9".to_string());
+        // The decimals of TOKEN are unknown, so its change stays unvalued
+        // instead of failing the others.
+        client.coin_metadata_cache().insert(
+            "0x2::shared::TOKEN",
+            CoinMetadata {
+                decimals: None,
+                symbol: String::new(),
+                name: String::new(),
+                icon_url: None,
+            },
+        );
+        let prices = Arc::new(RecordingPrices {
+            prices: StaticPriceSource::new()
+                .with("0x2::sui::SUI", usd("2.5"))
+                .with("0x2::other::COIN", usd("0.10"))
+                .with("0x2::shared::TOKEN", usd("3")),
+            requests: Mutex::default(),
+        });
+        let mut parsed = load_fixture();
+        parsed.balance_changes[0].coin_type =
+            "0x0000000000000000000000000000000000000000000000000000000000000002::sui::SUI"
+                .to_string();
+
+        let source: Arc<dyn PriceSource> = prices.clone();
+        client
+            .attach_valuations(&mut parsed, source.as_ref())
+            .await
+            .expect("valuation should succeed");
+
+        // SUI is priced once although the change and the gas fee spell it
+        // differently.
+        assert_eq!(
+            prices
+                .requests
+                .lock()
+                .expect("lock should not be poisoned")
+                .len(),
+            5
+        );
+
+        let values: Vec<Option<String>> = parsed
+            .balance_changes
+            .iter()
+            .map(|change| {
+                change
+                    .valuation
+                    .as_ref()
+                    .map(|valuation| valuation.usd_value.to_string())
+            })
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                Some("0.0000025000".to_string()),
+                Some("-0.7500".to_string()),
+                None,
+                None,
+                None,
+            ]
+        );
+        let gas = parsed.gas_fee_valuation.expect("gas should be valued");
+        assert_eq!(format!("{:.8}", gas.usd_value), "0.00000062");
+
+        // Failing to fetch metadata is not the same as unknown decimals.
+        let offline = TxParseClient::new("http://127.0.0.1:9");
+        let error = offline
+            .attach_valuations(&mut load_fixture(), source.as_ref())
+            .await
+            .expect_err("metadata requests should fail");
+        assert!(matches!(error, ClientError::Http(_)));
+    }
+
+    #[test]
+    fn parses_prices_in_exponent_form() {
+        assert_eq!(parse_price("1.2e-7"), Some(usd("0.00000012")));
+        assert_eq!(parse_price("2.5E3"), Some(usd("2500")));
+        assert_eq!(parse_price("125e-2"), Some(usd("1.25")));
+        assert_eq!(parse_price("0.10"), Some(usd("0.10")));
+        assert_eq!(parse_price("1e-300"), None);
+        assert_eq!(parse_price("1e"), None);
+        assert_eq!(parse_price("n/a"), None);
+    }
+
+    #[tokio::test]
+    async fn queries_an_rpc_oracle_at_the_transaction_time() {
+        if !ensure_socket_permission() {
+            eprintln!("skipping queries_an_rpc_oracle_at_the_transaction_time: binding to localhost is not permitted in this environment");
+            return;
+        }
+
+        let server = MockServer::start_async().await;
+        let sui = server
+            .mock_async(|when, then| {
+                when.method(POST).json_body_partial(
+                    r#"{"method": "oracle_getUsdPrice", "params": ["0x2::sui::SUI", 1700000000000]}"#,
+                );
+                then.status(200)
+                    .json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": "1.25" }));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .json_body_partial(r#"{"method": "oracle_getUsdPrice"}"#)
+                    .body_contains("0x2::other::COIN");
+                then.status(200)
+                    .json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": "n/a" }));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .json_body_partial(r#"{"method": "oracle_getUsdPrice"}"#)
+                    .body_contains("0x2::tiny::DUST");
+                then.status(200)
+                    .json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": 1.2e-7 }));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .json_body_partial(r#"{"method": "oracle_getUsdPrice"}"#)
+                    .body_contains("TOKEN");
+                then.status(200)
+                    .json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": null }));
+            })
+            .await;
+
+        let client = client_with_metadata(server.base_url());
+        let oracle = RpcPriceSource::new(client.clone(), "oracle_getUsdPrice");
+        assert_eq!(
+            oracle
+                .usd_price("0x2::sui::SUI", Some(1_700_000_000_000))
+                .await
+                .expect("price should load"),
+            Some(usd("1.25"))
+        );
+        assert_eq!(
+            oracle
+                .usd_price("0x2::tiny::DUST", None)
+                .await
+                .expect("a price in exponent form should load"),
+            Some(usd("0.00000012"))
+        );
+        assert_eq!(
+            oracle
+                .usd_price("0x2::shared::TOKEN", None)
+                .await
+                .expect("missing price is not an error"),
+            None
+        );
+
+        let mut parsed = load_fixture();
+        let error = client
+            .attach_valuations(&mut parsed, &oracle)
+            .await
+            .expect_err("invalid price should fail");
+        assert!(matches!(
+            error,
+            ClientError::InvalidPrice { ref coin_type, .. } if coin_type == "0x2::other::COIN"
+        ));
+        sui.assert_hits_async(2).await;
+    }
+}
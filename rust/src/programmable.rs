@@ -0,0 +1,581 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::address::{normalize_address, AddressFormat};
+use crate::parse::ParseError;
+use crate::type_tag::normalize_type;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Argument {
+    GasCoin,
+    Input(u16),
+    Result(u16),
+    NestedResult(u16, u16),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TransactionInput {
+    #[serde(rename_all = "camelCase")]
+    Pure {
+        value_type: Option<String>,
+        value: Value,
+    },
+    #[serde(rename_all = "camelCase")]
+    ImmOrOwnedObject {
+        object_id: String,
+        version: String,
+        digest: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    SharedObject {
+        object_id: String,
+        initial_shared_version: String,
+        mutable: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    Receiving {
+        object_id: String,
+        version: String,
+        digest: String,
+    },
+    /// An input kind this crate does not know yet, as the node rendered it.
+    Unknown {
+        value: Value,
+    },
+}
+
+impl TransactionInput {
+    pub fn object_id(&self) -> Option<&str> {
+        match self {
+            TransactionInput::Pure { .. } | TransactionInput::Unknown { .. } => None,
+            TransactionInput::ImmOrOwnedObject { object_id, .. }
+            | TransactionInput::SharedObject { object_id, .. }
+            | TransactionInput::Receiving { object_id, .. } => Some(object_id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveCall {
+    pub package: String,
+    pub module: String,
+    pub function: String,
+    pub type_arguments: Vec<String>,
+    pub arguments: Vec<Argument>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Command {
+    MoveCall(MoveCall),
+    #[serde(rename_all = "camelCase")]
+    SplitCoins {
+        coin: Argument,
+        amounts: Vec<Argument>,
+    },
+    #[serde(rename_all = "camelCase")]
+    MergeCoins {
+        destination: Argument,
+        sources: Vec<Argument>,
+    },
+    #[serde(rename_all = "camelCase")]
+    TransferObjects {
+        objects: Vec<Argument>,
+        address: Argument,
+    },
+    #[serde(rename_all = "camelCase")]
+    Publish {
+        dependencies: Vec<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Upgrade {
+        dependencies: Vec<String>,
+        package: String,
+        ticket: Argument,
+    },
+    #[serde(rename_all = "camelCase")]
+    MakeMoveVec {
+        element_type: Option<String>,
+        elements: Vec<Argument>,
+    },
+    /// A command kind this crate does not know yet, as the node rendered it.
+    /// Its arguments are not decoded.
+    Unknown {
+        value: Value,
+    },
+}
+
+impl Command {
+    /// Arguments consumed by the command, in the order the command lists them.
+    pub fn arguments(&self) -> Vec<Argument> {
+        match self {
+            Command::MoveCall(call) => call.arguments.clone(),
+            Command::SplitCoins { coin, amounts } => std::iter::once(*coin)
+                .chain(amounts.iter().copied())
+                .collect(),
+            Command::MergeCoins {
+                destination,
+                sources,
+            } => std::iter::once(*destination)
+                .chain(sources.iter().copied())
+                .collect(),
+            Command::TransferObjects { objects, address } => objects
+                .iter()
+                .copied()
+                .chain(std::iter::once(*address))
+                .collect(),
+            Command::Publish { .. } | Command::Unknown { .. } => Vec::new(),
+            Command::Upgrade { ticket, .. } => vec![*ticket],
+            Command::MakeMoveVec { elements, .. } => elements.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgrammableTransaction {
+    pub inputs: Vec<TransactionInput>,
+    pub commands: Vec<Command>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolvedArgument<'a> {
+    GasCoin,
+    Input {
+        index: u16,
+        input: &'a TransactionInput,
+    },
+    Result {
+        command_index: u16,
+        command: &'a Command,
+    },
+    NestedResult {
+        command_index: u16,
+        result_index: u16,
+        command: &'a Command,
+    },
+}
+
+impl ProgrammableTransaction {
+    pub(crate) fn normalize_addresses(&mut self, format: AddressFormat) {
+        for input in &mut self.inputs {
+            match input {
+                TransactionInput::Pure { .. } | TransactionInput::Unknown { .. } => {}
+                TransactionInput::ImmOrOwnedObject { object_id, .. }
+                | TransactionInput::SharedObject { object_id, .. }
+                | TransactionInput::Receiving { object_id, .. } => {
+                    normalize_address(object_id, format)
+                }
+            }
+        }
+
+        for command in &mut self.commands {
+            match command {
+                Command::MoveCall(call) => {
+                    normalize_address(&mut call.package, format);
+                    for type_argument in &mut call.type_arguments {
+                        normalize_type(type_argument, format);
+                    }
+                }
+                Command::Publish { dependencies } => {
+                    for dependency in dependencies {
+                        normalize_address(dependency, format);
+                    }
+                }
+                Command::Upgrade {
+                    dependencies,
+                    package,
+                    ..
+                } => {
+                    normalize_address(package, format);
+                    for dependency in dependencies {
+                        normalize_address(dependency, format);
+                    }
+                }
+                Command::MakeMoveVec {
+                    element_type: Some(element_type),
+                    ..
+                } => normalize_type(element_type, format),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn resolve(&self, argument: Argument) -> Option<ResolvedArgument<'_>> {
+        match argument {
+            Argument::GasCoin => Some(ResolvedArgument::GasCoin),
+            Argument::Input(index) => self
+                .inputs
+                .get(index as usize)
+                .map(|input| ResolvedArgument::Input { index, input }),
+            Argument::Result(command_index) => {
+                self.commands
+                    .get(command_index as usize)
+                    .map(|command| ResolvedArgument::Result {
+                        command_index,
+                        command,
+                    })
+            }
+            Argument::NestedResult(command_index, result_index) => self
+                .commands
+                .get(command_index as usize)
+                .map(|command| ResolvedArgument::NestedResult {
+                    command_index,
+                    result_index,
+                    command,
+                }),
+        }
+    }
+
+    pub fn move_calls(&self) -> impl Iterator<Item = &MoveCall> {
+        self.commands.iter().filter_map(|command| match command {
+            Command::MoveCall(call) => Some(call),
+            _ => None,
+        })
+    }
+
+    /// Indices of the earlier commands whose results feed `command_index`.
+    pub fn dependencies(&self, command_index: usize) -> Vec<usize> {
+        let Some(command) = self.commands.get(command_index) else {
+            return Vec::new();
+        };
+
+        let mut dependencies: Vec<usize> = command
+            .arguments()
+            .into_iter()
+            .filter_map(|argument| match argument {
+                Argument::Result(index) | Argument::NestedResult(index, _) => Some(index as usize),
+                _ => None,
+            })
+            .collect();
+        dependencies.sort_unstable();
+        dependencies.dedup();
+        dependencies
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind")]
+pub(crate) enum RawTransactionKind {
+    ProgrammableTransaction {
+        #[serde(default)]
+        inputs: Vec<RawTransactionInput>,
+        #[serde(default)]
+        transactions: Vec<RawCommand>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum RawTransactionInput {
+    #[serde(rename_all = "camelCase")]
+    Pure {
+        value_type: Option<String>,
+        value: Value,
+    },
+    Object(RawObjectInput),
+    #[serde(untagged)]
+    Other(Value),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "objectType", rename_all = "camelCase")]
+pub(crate) enum RawObjectInput {
+    #[serde(rename_all = "camelCase")]
+    ImmOrOwnedObject {
+        object_id: String,
+        version: String,
+        digest: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    SharedObject {
+        object_id: String,
+        initial_shared_version: String,
+        mutable: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    Receiving {
+        object_id: String,
+        version: String,
+        digest: String,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct RawMoveCall {
+    pub package: String,
+    pub module: String,
+    pub function: String,
+    #[serde(default)]
+    pub type_arguments: Vec<String>,
+    #[serde(default)]
+    pub arguments: Vec<Argument>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) enum RawCommand {
+    MoveCall(RawMoveCall),
+    SplitCoins(Argument, Vec<Argument>),
+    MergeCoins(Argument, Vec<Argument>),
+    TransferObjects(Vec<Argument>, Argument),
+    Publish(Vec<String>),
+    Upgrade(Vec<String>, String, Argument),
+    MakeMoveVec(Option<String>, Vec<Argument>),
+    #[serde(untagged)]
+    Other(Value),
+}
+
+pub(crate) fn parse_programmable_transaction(
+    kind: &RawTransactionKind,
+) -> Result<Option<ProgrammableTransaction>, ParseError> {
+    let RawTransactionKind::ProgrammableTransaction {
+        inputs,
+        transactions,
+    } = kind
+    else {
+        return Ok(None);
+    };
+
+    let transaction = ProgrammableTransaction {
+        inputs: inputs.iter().map(parse_input).collect(),
+        commands: transactions.iter().map(parse_command).collect(),
+    };
+
+    for (index, command) in transaction.commands.iter().enumerate() {
+        for argument in command.arguments() {
+            let valid = match argument {
+                Argument::GasCoin => true,
+                Argument::Input(input) => (input as usize) < transaction.inputs.len(),
+                Argument::Result(result) | Argument::NestedResult(result, _) => {
+                    (result as usize) < index
+                }
+            };
+
+            if !valid {
+                return Err(ParseError::InvalidArgument {
+                    command: index,
+                    argument,
+                });
+            }
+        }
+    }
+
+    Ok(Some(transaction))
+}
+
+fn parse_input(input: &RawTransactionInput) -> TransactionInput {
+    match input.clone() {
+        RawTransactionInput::Pure { value_type, value } => {
+            TransactionInput::Pure { value_type, value }
+        }
+        RawTransactionInput::Object(RawObjectInput::ImmOrOwnedObject {
+            object_id,
+            version,
+            digest,
+        }) => TransactionInput::ImmOrOwnedObject {
+            object_id,
+            version,
+            digest,
+        },
+        RawTransactionInput::Object(RawObjectInput::SharedObject {
+            object_id,
+            initial_shared_version,
+            mutable,
+        }) => TransactionInput::SharedObject {
+            object_id,
+            initial_shared_version,
+            mutable,
+        },
+        RawTransactionInput::Object(RawObjectInput::Receiving {
+            object_id,
+            version,
+            digest,
+        }) => TransactionInput::Receiving {
+            object_id,
+            version,
+            digest,
+        },
+        RawTransactionInput::Other(value) => TransactionInput::Unknown { value },
+    }
+}
+
+fn parse_command(command: &RawCommand) -> Command {
+    match command.clone() {
+        RawCommand::MoveCall(call) => Command::MoveCall(MoveCall {
+            package: call.package,
+            module: call.module,
+            function: call.function,
+            type_arguments: call.type_arguments,
+            arguments: call.arguments,
+        }),
+        RawCommand::SplitCoins(coin, amounts) => Command::SplitCoins { coin, amounts },
+        RawCommand::MergeCoins(destination, sources) => Command::MergeCoins {
+            destination,
+            sources,
+        },
+        RawCommand::TransferObjects(objects, address) => {
+            Command::TransferObjects { objects, address }
+        }
+        RawCommand::Publish(dependencies) => Command::Publish { dependencies },
+        RawCommand::Upgrade(dependencies, package, ticket) => Command::Upgrade {
+            dependencies,
+            package,
+            ticket,
+        },
+        RawCommand::MakeMoveVec(element_type, elements) => Command::MakeMoveVec {
+            element_type,
+            elements,
+        },
+        RawCommand::Other(value) => Command::Unknown { value },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn decode(kind: Value) -> Result<Option<ProgrammableTransaction>, ParseError> {
+        let kind: RawTransactionKind =
+            serde_json::from_value(kind).expect("kind should deserialize");
+        parse_programmable_transaction(&kind)
+    }
+
+    #[test]
+    fn decodes_every_command_kind() {
+        let transaction = decode(json!({
+            "kind": "ProgrammableTransaction",
+            "inputs": [
+                { "type": "object", "objectType": "immOrOwnedObject", "objectId": "0x1a", "version": "3", "digest": "D1" },
+                { "type": "object", "objectType": "receiving", "objectId": "0x1b", "version": "4", "digest": "D2" },
+                { "type": "pure", "valueType": "u64", "value": "5" },
+            ],
+            "transactions": [
+                { "MergeCoins": [{ "Input": 0 }, [{ "Input": 1 }]] },
+                { "MakeMoveVec": [null, [{ "Input": 2 }]] },
+                { "Publish": ["0x1", "0x2"] },
+                { "Upgrade": [["0x1"], "0xfeed", { "Result": 1 }] },
+            ],
+        }))
+        .expect("decode should succeed")
+        .expect("transaction should be programmable");
+
+        assert_eq!(transaction.inputs[1].object_id(), Some("0x1b"));
+        assert_eq!(
+            transaction.commands[0],
+            Command::MergeCoins {
+                destination: Argument::Input(0),
+                sources: vec![Argument::Input(1)],
+            }
+        );
+        assert_eq!(
+            transaction.commands[1],
+            Command::MakeMoveVec {
+                element_type: None,
+                elements: vec![Argument::Input(2)],
+            }
+        );
+        assert_eq!(
+            transaction.commands[2],
+            Command::Publish {
+                dependencies: vec!["0x1".into(), "0x2".into()],
+            }
+        );
+        assert_eq!(transaction.dependencies(3), vec![1]);
+    }
+
+    #[test]
+    fn resolves_arguments_to_inputs_and_results() {
+        let transaction = decode(json!({
+            "kind": "ProgrammableTransaction",
+            "inputs": [{ "type": "pure", "valueType": "u64", "value": "5" }],
+            "transactions": [
+                { "SplitCoins": ["GasCoin", [{ "Input": 0 }]] },
+                { "TransferObjects": [[{ "NestedResult": [0, 0] }], { "Input": 0 }] },
+            ],
+        }))
+        .expect("decode should succeed")
+        .expect("transaction should be programmable");
+
+        assert_eq!(
+            transaction.resolve(Argument::GasCoin),
+            Some(ResolvedArgument::GasCoin)
+        );
+        assert!(matches!(
+            transaction.resolve(Argument::Input(0)),
+            Some(ResolvedArgument::Input {
+                index: 0,
+                input: TransactionInput::Pure { .. }
+            })
+        ));
+        assert!(matches!(
+            transaction.resolve(Argument::NestedResult(0, 0)),
+            Some(ResolvedArgument::NestedResult {
+                command: Command::SplitCoins { .. },
+                ..
+            })
+        ));
+        assert_eq!(transaction.resolve(Argument::Input(4)), None);
+    }
+
+    #[test]
+    fn rejects_forward_result_references() {
+        let err = decode(json!({
+            "kind": "ProgrammableTransaction",
+            "inputs": [],
+            "transactions": [{ "MakeMoveVec": ["u64", [{ "Result": 0 }]] }],
+        }))
+        .expect_err("self reference should fail");
+
+        assert!(matches!(
+            err,
+            ParseError::InvalidArgument {
+                command: 0,
+                argument: Argument::Result(0)
+            }
+        ));
+    }
+
+    #[test]
+    fn keeps_unknown_commands_and_inputs() {
+        let transaction = decode(json!({
+            "kind": "ProgrammableTransaction",
+            "inputs": [
+                { "type": "fundsWithdrawal", "amount": "10" },
+                { "type": "object", "objectType": "futureObject", "objectId": "0x1a" },
+            ],
+            "transactions": [
+                { "FutureCommand": [{ "Input": 0 }] },
+                { "MakeMoveVec": [null, [{ "Result": 0 }]] },
+            ],
+        }))
+        .expect("unknown kinds should decode")
+        .expect("transaction should be programmable");
+
+        assert_eq!(
+            transaction.inputs[0],
+            TransactionInput::Unknown {
+                value: json!({ "type": "fundsWithdrawal", "amount": "10" }),
+            }
+        );
+        assert!(matches!(transaction.inputs[1], TransactionInput::Unknown { .. }));
+        assert_eq!(
+            transaction.commands[0],
+            Command::Unknown {
+                value: json!({ "FutureCommand": [{ "Input": 0 }] }),
+            }
+        );
+        assert_eq!(transaction.dependencies(1), vec![0]);
+    }
+
+    #[test]
+    fn ignores_system_transactions() {
+        let decoded = decode(json!({ "kind": "ConsensusCommitPrologue", "epoch": "1" }))
+            .expect("system transactions should decode");
+        assert!(decoded.is_none());
+    }
+}
@@ -0,0 +1,150 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// Request budget for a single endpoint.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// Sustained request rate. Rates that are not positive and finite, such
+    /// as zero, infinity or NaN, leave requests unlimited.
+    pub requests_per_second: f64,
+    /// Number of requests that may be sent back to back before the rate
+    /// applies.
+    pub burst: u32,
+    /// Upper bound on concurrent in-flight requests, if any.
+    pub max_in_flight: Option<usize>,
+}
+
+impl RateLimit {
+    pub fn per_second(requests_per_second: f64) -> Self {
+        Self {
+            requests_per_second,
+            burst: 1,
+            max_in_flight: None,
+        }
+    }
+
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = burst;
+        self
+    }
+
+    pub fn with_max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = Some(max_in_flight);
+        self
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    limit: RateLimit,
+    bucket: Mutex<TokenBucket>,
+    in_flight: Option<Semaphore>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            bucket: Mutex::new(TokenBucket {
+                tokens: limit.burst.max(1) as f64,
+                refilled_at: Instant::now(),
+            }),
+            in_flight: limit.max_in_flight.map(|max| Semaphore::new(max.max(1))),
+        }
+    }
+
+    /// Waits until a request may be sent. The returned permit must be held
+    /// for as long as the request is in flight.
+    pub(crate) async fn acquire(&self) -> Option<SemaphorePermit<'_>> {
+        let permit = match &self.in_flight {
+            Some(semaphore) => semaphore.acquire().await.ok(),
+            None => None,
+        };
+
+        while let Some(wait) = self.take_token() {
+            tokio::time::sleep(wait).await;
+        }
+
+        permit
+    }
+
+    /// Takes a token if one is available, otherwise returns how long to wait
+    /// for the next one.
+    fn take_token(&self) -> Option<Duration> {
+        let rate = self.limit.requests_per_second;
+        if !(rate > 0.0 && rate.is_finite()) {
+            return None;
+        }
+
+        let mut bucket = self
+            .bucket
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let now = Instant::now();
+        let capacity = self.limit.burst.max(1) as f64;
+        let refill = now.duration_since(bucket.refilled_at).as_secs_f64() * rate;
+        bucket.tokens = (bucket.tokens + refill).min(capacity);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            None
+        } else {
+            // Tiny rates give waits beyond what a `Duration` can hold.
+            Some(Duration::try_from_secs_f64((1.0 - bucket.tokens) / rate).unwrap_or(Duration::MAX))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn spaces_requests_after_the_burst() {
+        let limiter = RateLimiter::new(RateLimit::per_second(50.0).with_burst(2));
+        let started = Instant::now();
+        for _ in 0..6 {
+            limiter.acquire().await;
+        }
+
+        // Two requests go out immediately, the other four wait 20ms each.
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(75), "took {elapsed:?}");
+    }
+
+    #[test]
+    fn tolerates_degenerate_rates() {
+        for rate in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            let limiter = RateLimiter::new(RateLimit::per_second(rate));
+            assert_eq!(limiter.take_token(), None, "{rate}");
+            assert_eq!(limiter.take_token(), None, "{rate}");
+        }
+
+        let limiter = RateLimiter::new(RateLimit::per_second(1e-300));
+        assert_eq!(limiter.take_token(), None);
+        assert_eq!(limiter.take_token(), Some(Duration::MAX));
+    }
+
+    #[tokio::test]
+    async fn bounds_in_flight_requests() {
+        let limiter = RateLimiter::new(RateLimit::per_second(1000.0).with_max_in_flight(1));
+        let first = limiter.acquire().await;
+        assert!(first.is_some());
+
+        let blocked = tokio::time::timeout(Duration::from_millis(20), limiter.acquire()).await;
+        assert!(blocked.is_err());
+
+        drop(first);
+        let next = tokio::time::timeout(Duration::from_millis(20), limiter.acquire()).await;
+        assert!(matches!(next, Ok(Some(_))));
+    }
+}
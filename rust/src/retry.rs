@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use crate::client::ClientError;
+
+/// Controls how [`TxParseClient`](crate::TxParseClient) retries failed
+/// JSON-RPC requests.
+///
+/// Transport errors such as timeouts and refused connections are always
+/// retried; HTTP statuses and JSON-RPC error codes only when listed here.
+/// Clients do not retry unless a policy is set through
+/// [`TxParseClientBuilder::retry_policy`](crate::TxParseClientBuilder::retry_policy);
+/// the default policy makes up to three attempts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one.
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: f64,
+    /// Randomizes each delay between half and all of the computed backoff.
+    pub jitter: bool,
+    pub retry_statuses: Vec<u16>,
+    pub retry_rpc_codes: Vec<i64>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 2.0,
+            jitter: true,
+            retry_statuses: vec![408, 429, 500, 502, 503, 504],
+            retry_rpc_codes: vec![-32603],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that sends every request exactly once.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn should_retry(&self, error: &ClientError) -> bool {
+        match error {
+            ClientError::Http(error) => error.is_timeout() || error.is_connect(),
+            ClientError::HttpStatus { status } => self.retry_statuses.contains(status),
+            ClientError::Rpc { code, .. } => self.retry_rpc_codes.contains(code),
+            _ => false,
+        }
+    }
+
+    /// Delay before the attempt following `attempt` (1-based).
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let delay = self
+            .initial_backoff
+            .mul_f64(self.multiplier.max(1.0).powi(exponent).min(u32::MAX as f64))
+            .min(self.max_backoff);
+
+        if self.jitter {
+            delay.mul_f64(0.5 + fastrand::f64() * 0.5)
+        } else {
+            delay
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_grows_exponentially_up_to_the_cap() {
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(350),
+            jitter: false,
+            ..RetryPolicy::default()
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(350));
+        assert_eq!(policy.backoff(200), Duration::from_millis(350));
+
+        let jittered = RetryPolicy {
+            jitter: true,
+            ..policy
+        };
+        for attempt in 1..5 {
+            let delay = jittered.backoff(attempt);
+            assert!(delay <= Duration::from_millis(350));
+            assert!(delay >= Duration::from_millis(50));
+        }
+    }
+}
@@ -0,0 +1,607 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+use crate::address::{AddressFormat, SuiAddress};
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TypeTagParseError {
+    #[error("type string ended unexpectedly")]
+    UnexpectedEnd,
+    #[error("unexpected token {token:?} at offset {offset}")]
+    UnexpectedToken { token: String, offset: usize },
+    #[error("invalid address {0:?}")]
+    InvalidAddress(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TypeTag {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    Address,
+    Signer,
+    Vector(Box<TypeTag>),
+    Struct(Box<StructTag>),
+}
+
+/// A fully qualified Move struct type. The address is parsed into a
+/// `SuiAddress` so that `0x2` and `0x000…002` compare equal.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StructTag {
+    pub address: SuiAddress,
+    pub module: String,
+    pub name: String,
+    pub type_params: Vec<TypeTag>,
+}
+
+impl TypeTag {
+    pub fn as_struct(&self) -> Option<&StructTag> {
+        match self {
+            TypeTag::Struct(tag) => Some(tag),
+            _ => None,
+        }
+    }
+
+    /// Finds the coin type `T` of the first `Coin<T>` or `Balance<T>` nested
+    /// anywhere in this type, e.g. inside `dynamic_field::Field<K, Coin<T>>`.
+    pub fn find_coin_type(&self) -> Option<&TypeTag> {
+        match self {
+            TypeTag::Struct(tag) => tag.coin_type().or_else(|| {
+                tag.type_params
+                    .iter()
+                    .find_map(|param| param.find_coin_type())
+            }),
+            TypeTag::Vector(inner) => inner.find_coin_type(),
+            _ => None,
+        }
+    }
+
+    /// Renders the type with every address in `format`. `Display` uses
+    /// `AddressFormat::Long`.
+    pub fn to_canonical_string(&self, format: AddressFormat) -> String {
+        let mut out = String::new();
+        write_type_tag(&mut out, self, format);
+        out
+    }
+
+    /// Converts a type from `sui_getNormalizedMoveStruct` output, such as
+    /// `"U64"` or `{"Struct": {...}}`, substituting `type_args` for
+    /// `{"TypeParameter": i}`.
+    pub(crate) fn from_normalized(value: &Value, type_args: &[TypeTag]) -> Option<TypeTag> {
+        let map = match value {
+            Value::String(name) => {
+                return match name.as_str() {
+                    "Bool" => Some(TypeTag::Bool),
+                    "U8" => Some(TypeTag::U8),
+                    "U16" => Some(TypeTag::U16),
+                    "U32" => Some(TypeTag::U32),
+                    "U64" => Some(TypeTag::U64),
+                    "U128" => Some(TypeTag::U128),
+                    "U256" => Some(TypeTag::U256),
+                    "Address" => Some(TypeTag::Address),
+                    "Signer" => Some(TypeTag::Signer),
+                    _ => None,
+                }
+            }
+            Value::Object(map) => map,
+            _ => return None,
+        };
+
+        if let Some(index) = map.get("TypeParameter") {
+            return type_args
+                .get(usize::try_from(index.as_u64()?).ok()?)
+                .cloned();
+        }
+        if let Some(inner) = map.get("Vector") {
+            return Some(TypeTag::Vector(Box::new(Self::from_normalized(
+                inner, type_args,
+            )?)));
+        }
+
+        let tag = map.get("Struct")?;
+        let type_params = tag
+            .get("typeArguments")
+            .and_then(Value::as_array)
+            .map(|args| {
+                args.iter()
+                    .map(|arg| Self::from_normalized(arg, type_args))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .unwrap_or(Some(Vec::new()))?;
+        Some(TypeTag::Struct(Box::new(StructTag {
+            address: tag.get("address")?.as_str()?.parse().ok()?,
+            module: tag.get("module")?.as_str()?.to_string(),
+            name: tag.get("name")?.as_str()?.to_string(),
+            type_params,
+        })))
+    }
+}
+
+fn write_type_tag(out: &mut String, tag: &TypeTag, format: AddressFormat) {
+    match tag {
+        TypeTag::Bool => out.push_str("bool"),
+        TypeTag::U8 => out.push_str("u8"),
+        TypeTag::U16 => out.push_str("u16"),
+        TypeTag::U32 => out.push_str("u32"),
+        TypeTag::U64 => out.push_str("u64"),
+        TypeTag::U128 => out.push_str("u128"),
+        TypeTag::U256 => out.push_str("u256"),
+        TypeTag::Address => out.push_str("address"),
+        TypeTag::Signer => out.push_str("signer"),
+        TypeTag::Vector(inner) => {
+            out.push_str("vector<");
+            write_type_tag(out, inner, format);
+            out.push('>');
+        }
+        TypeTag::Struct(tag) => write_struct_tag(out, tag, format),
+    }
+}
+
+fn write_struct_tag(out: &mut String, tag: &StructTag, format: AddressFormat) {
+    out.push_str(&tag.address.format(format));
+    out.push_str("::");
+    out.push_str(&tag.module);
+    out.push_str("::");
+    out.push_str(&tag.name);
+
+    if !tag.type_params.is_empty() {
+        out.push('<');
+        for (index, param) in tag.type_params.iter().enumerate() {
+            if index > 0 {
+                out.push_str(", ");
+            }
+            write_type_tag(out, param, format);
+        }
+        out.push('>');
+    }
+}
+
+impl StructTag {
+    pub fn is(&self, address: &str, module: &str, name: &str) -> bool {
+        self.module == module
+            && self.name == name
+            && address
+                .parse::<SuiAddress>()
+                .is_ok_and(|address| address == self.address)
+    }
+
+    pub fn to_canonical_string(&self, format: AddressFormat) -> String {
+        let mut out = String::new();
+        write_struct_tag(&mut out, self, format);
+        out
+    }
+
+    /// Returns `T` when this is `0x2::coin::Coin<T>` or `0x2::balance::Balance<T>`.
+    pub fn coin_type(&self) -> Option<&TypeTag> {
+        if self.is("0x2", "coin", "Coin") || self.is("0x2", "balance", "Balance") {
+            self.type_params.first()
+        } else {
+            None
+        }
+    }
+
+    /// Matches `pattern` against this struct. A pattern without type
+    /// parameters matches every instantiation of the same struct.
+    pub fn matches(&self, pattern: &StructTag) -> bool {
+        self.address == pattern.address
+            && self.module == pattern.module
+            && self.name == pattern.name
+            && (pattern.type_params.is_empty() || self.type_params == pattern.type_params)
+    }
+}
+
+/// Compares two type strings structurally, ignoring address padding. Falls
+/// back to string comparison when either side is not a valid struct type.
+pub(crate) fn type_matches(actual: &str, pattern: &str) -> bool {
+    match (actual.parse::<StructTag>(), pattern.parse::<StructTag>()) {
+        (Ok(actual), Ok(pattern)) => actual.matches(&pattern),
+        _ => {
+            actual == pattern
+                || (!pattern.contains('<')
+                    && actual
+                        .split_once('<')
+                        .is_some_and(|(base, _)| base == pattern))
+        }
+    }
+}
+
+/// Rewrites every address inside a type string in `format`, leaving strings
+/// that are not valid Move types untouched.
+pub(crate) fn normalize_type(value: &mut String, format: AddressFormat) {
+    if let Ok(tag) = value.parse::<TypeTag>() {
+        *value = tag.to_canonical_string(format);
+    }
+}
+
+/// Finds `target` inside the type string `input` and returns it as `input`
+/// renders it, so that addresses keep the node's format.
+pub(crate) fn find_rendering<'a>(input: &'a str, target: &TypeTag) -> Option<&'a str> {
+    let mut parser = Parser::new(input).ok()?;
+    for start in 0..parser.tokens.len() {
+        parser.position = start;
+        if parser.type_tag().is_ok_and(|tag| &tag == target) {
+            let (end, token) = parser.tokens[parser.position - 1];
+            return Some(&input[parser.tokens[start].0..end + token.len()]);
+        }
+    }
+    None
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    Word(&'a str),
+    ColonColon,
+    Lt,
+    Gt,
+    Comma,
+}
+
+impl Token<'_> {
+    fn len(&self) -> usize {
+        match self {
+            Token::Word(word) => word.len(),
+            Token::ColonColon => 2,
+            Token::Lt | Token::Gt | Token::Comma => 1,
+        }
+    }
+}
+
+struct Parser<'a> {
+    tokens: Vec<(usize, Token<'a>)>,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Result<Self, TypeTagParseError> {
+        let mut tokens = Vec::new();
+        let mut chars = input.char_indices().peekable();
+
+        while let Some(&(offset, c)) = chars.peek() {
+            match c {
+                c if c.is_whitespace() => {
+                    chars.next();
+                }
+                '<' | '>' | ',' => {
+                    chars.next();
+                    let token = match c {
+                        '<' => Token::Lt,
+                        '>' => Token::Gt,
+                        _ => Token::Comma,
+                    };
+                    tokens.push((offset, token));
+                }
+                ':' => {
+                    chars.next();
+                    match chars.next() {
+                        Some((_, ':')) => tokens.push((offset, Token::ColonColon)),
+                        _ => {
+                            return Err(TypeTagParseError::UnexpectedToken {
+                                token: ":".to_string(),
+                                offset,
+                            })
+                        }
+                    }
+                }
+                c if c.is_ascii_alphanumeric() || c == '_' => {
+                    let mut end = offset;
+                    while let Some(&(index, c)) = chars.peek() {
+                        if !(c.is_ascii_alphanumeric() || c == '_') {
+                            break;
+                        }
+                        end = index + c.len_utf8();
+                        chars.next();
+                    }
+                    tokens.push((offset, Token::Word(&input[offset..end])));
+                }
+                other => {
+                    return Err(TypeTagParseError::UnexpectedToken {
+                        token: other.to_string(),
+                        offset,
+                    })
+                }
+            }
+        }
+
+        Ok(Self {
+            tokens,
+            position: 0,
+        })
+    }
+
+    fn next(&mut self) -> Result<(usize, Token<'a>), TypeTagParseError> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .copied()
+            .ok_or(TypeTagParseError::UnexpectedEnd)?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.position).map(|(_, token)| *token)
+    }
+
+    fn expect(&mut self, expected: Token<'a>) -> Result<(), TypeTagParseError> {
+        let (offset, token) = self.next()?;
+        if token == expected {
+            Ok(())
+        } else {
+            Err(unexpected(offset, token))
+        }
+    }
+
+    fn word(&mut self) -> Result<&'a str, TypeTagParseError> {
+        match self.next()? {
+            (_, Token::Word(word)) => Ok(word),
+            (offset, token) => Err(unexpected(offset, token)),
+        }
+    }
+
+    fn finish(&self) -> Result<(), TypeTagParseError> {
+        match self.tokens.get(self.position) {
+            None => Ok(()),
+            Some(&(offset, token)) => Err(unexpected(offset, token)),
+        }
+    }
+
+    fn type_tag(&mut self) -> Result<TypeTag, TypeTagParseError> {
+        let word = self.word()?;
+
+        if self.peek() == Some(Token::ColonColon) {
+            return Ok(TypeTag::Struct(Box::new(self.struct_tag_after(word)?)));
+        }
+
+        Ok(match word {
+            "bool" => TypeTag::Bool,
+            "u8" => TypeTag::U8,
+            "u16" => TypeTag::U16,
+            "u32" => TypeTag::U32,
+            "u64" => TypeTag::U64,
+            "u128" => TypeTag::U128,
+            "u256" => TypeTag::U256,
+            "address" => TypeTag::Address,
+            "signer" => TypeTag::Signer,
+            "vector" => {
+                self.expect(Token::Lt)?;
+                let inner = self.type_tag()?;
+                self.expect(Token::Gt)?;
+                TypeTag::Vector(Box::new(inner))
+            }
+            other => {
+                let offset = self.tokens[self.position - 1].0;
+                return Err(TypeTagParseError::UnexpectedToken {
+                    token: other.to_string(),
+                    offset,
+                });
+            }
+        })
+    }
+
+    fn struct_tag(&mut self) -> Result<StructTag, TypeTagParseError> {
+        let address = self.word()?;
+        self.struct_tag_after(address)
+    }
+
+    fn struct_tag_after(&mut self, address: &str) -> Result<StructTag, TypeTagParseError> {
+        // `type_name` renders addresses without the prefix, e.g. in the
+        // keys of bags indexed by coin type.
+        let address = SuiAddress::parse_unprefixed(address)
+            .map_err(|_| TypeTagParseError::InvalidAddress(address.to_string()))?;
+        self.expect(Token::ColonColon)?;
+        let module = self.word()?.to_string();
+        self.expect(Token::ColonColon)?;
+        let name = self.word()?.to_string();
+
+        let mut type_params = Vec::new();
+        if self.peek() == Some(Token::Lt) {
+            self.next()?;
+            loop {
+                type_params.push(self.type_tag()?);
+                match self.next()? {
+                    (_, Token::Comma) => continue,
+                    (_, Token::Gt) => break,
+                    (offset, token) => return Err(unexpected(offset, token)),
+                }
+            }
+        }
+
+        Ok(StructTag {
+            address,
+            module,
+            name,
+            type_params,
+        })
+    }
+}
+
+fn unexpected(offset: usize, token: Token<'_>) -> TypeTagParseError {
+    let token = match token {
+        Token::Word(word) => word.to_string(),
+        Token::ColonColon => "::".to_string(),
+        Token::Lt => "<".to_string(),
+        Token::Gt => ">".to_string(),
+        Token::Comma => ",".to_string(),
+    };
+    TypeTagParseError::UnexpectedToken { token, offset }
+}
+
+impl FromStr for TypeTag {
+    type Err = TypeTagParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s)?;
+        let tag = parser.type_tag()?;
+        parser.finish()?;
+        Ok(tag)
+    }
+}
+
+impl FromStr for StructTag {
+    type Err = TypeTagParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s)?;
+        let tag = parser.struct_tag()?;
+        parser.finish()?;
+        Ok(tag)
+    }
+}
+
+impl fmt::Display for TypeTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_canonical_string(AddressFormat::Long))
+    }
+}
+
+impl fmt::Display for StructTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_canonical_string(AddressFormat::Long))
+    }
+}
+
+impl Serialize for TypeTag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for TypeTag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        raw.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for StructTag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for StructTag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        raw.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUI: &str =
+        "0x0000000000000000000000000000000000000000000000000000000000000002::sui::SUI";
+
+    #[test]
+    fn parses_and_normalizes_struct_tags() {
+        let tag: StructTag = "0x2::coin::Coin<0x2::sui::SUI>"
+            .parse()
+            .expect("tag should parse");
+
+        assert_eq!(tag.module, "coin");
+        assert_eq!(tag.name, "Coin");
+        assert!(tag.is("0x2", "coin", "Coin"));
+        assert_eq!(
+            tag.coin_type().map(ToString::to_string).as_deref(),
+            Some(SUI)
+        );
+        assert_eq!(
+            tag.to_string(),
+            format!("0x0000000000000000000000000000000000000000000000000000000000000002::coin::Coin<{SUI}>")
+        );
+    }
+
+    #[test]
+    fn parses_nested_generics_vectors_and_primitives() {
+        let tag: TypeTag = "0x2::dynamic_field::Field<vector<u8>, 0x2::coin::Coin<0xabc::lp::LP<0x2::sui::SUI, 0xdef::usdc::USDC>>>"
+            .parse()
+            .expect("nested tag should parse");
+
+        let field = tag.as_struct().expect("tag should be a struct");
+        assert_eq!(field.type_params[0], TypeTag::Vector(Box::new(TypeTag::U8)));
+
+        let coin_type = tag.find_coin_type().expect("coin type should be found");
+        let lp = coin_type.as_struct().expect("coin type should be a struct");
+        assert_eq!(lp.name, "LP");
+        assert_eq!(lp.type_params.len(), 2);
+        assert_eq!(lp.type_params[0].to_string(), SUI);
+    }
+
+    #[test]
+    fn renders_short_addresses() {
+        let tag: TypeTag = format!("0x2::coin::Coin<{SUI}>")
+            .parse()
+            .expect("tag should parse");
+
+        assert_eq!(
+            tag.to_canonical_string(AddressFormat::Short),
+            "0x2::coin::Coin<0x2::sui::SUI>"
+        );
+
+        let mut raw = "vector<0x02::sui::SUI>".to_string();
+        normalize_type(&mut raw, AddressFormat::Short);
+        assert_eq!(raw, "vector<0x2::sui::SUI>");
+    }
+
+    #[test]
+    fn rejects_malformed_types() {
+        assert_eq!(
+            "0x2::coin::Coin<0x2::sui::SUI".parse::<TypeTag>(),
+            Err(TypeTagParseError::UnexpectedEnd)
+        );
+        assert!(matches!(
+            "0xzz::coin::Coin".parse::<TypeTag>(),
+            Err(TypeTagParseError::InvalidAddress(_))
+        ));
+        assert!(matches!(
+            "u64>".parse::<TypeTag>(),
+            Err(TypeTagParseError::UnexpectedToken { offset: 3, .. })
+        ));
+        assert!("u64".parse::<StructTag>().is_err());
+    }
+
+    #[test]
+    fn matches_types_ignoring_address_padding() {
+        let actual = "0x0000000000000000000000000000000000000000000000000000000000000abc::pool::SwapEvent<0x2::sui::SUI>";
+
+        assert!(type_matches(actual, "0xabc::pool::SwapEvent"));
+        assert!(type_matches(
+            actual,
+            "0xabc::pool::SwapEvent<0x02::sui::SUI>"
+        ));
+        assert!(!type_matches(actual, "0xabc::pool::SwapEvent<u64>"));
+        assert!(!type_matches(actual, "0xabd::pool::SwapEvent"));
+    }
+
+    #[test]
+    fn converts_normalized_types() {
+        let normalized = serde_json::json!({
+            "Struct": {
+                "address": "0x2",
+                "module": "balance",
+                "name": "Balance",
+                "typeArguments": [{ "TypeParameter": 1 }],
+            }
+        });
+        let args = vec![TypeTag::U8, SUI.parse().expect("SUI should parse")];
+
+        let tag = TypeTag::from_normalized(&normalized, &args).expect("type should convert");
+        assert_eq!(tag.to_string(), format!("0x0000000000000000000000000000000000000000000000000000000000000002::balance::Balance<{SUI}>"));
+        assert_eq!(
+            TypeTag::from_normalized(&serde_json::json!({ "Vector": "U64" }), &[]),
+            Some(TypeTag::Vector(Box::new(TypeTag::U64)))
+        );
+        assert_eq!(
+            TypeTag::from_normalized(&serde_json::json!({ "TypeParameter": 2 }), &args),
+            None
+        );
+    }
+}
@@ -1,38 +1,635 @@
-use serde::{Deserialize, Serialize};
+use num_bigint::{BigInt, BigUint};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
+use std::fmt;
+
+use crate::address::{normalize_address, AddressFormat};
+use crate::amount::CoinAmount;
+use crate::coin_metadata::CoinMetadata;
+use crate::price::Valuation;
+use crate::programmable::{ProgrammableTransaction, RawTransactionKind};
+use crate::type_tag::{normalize_type, type_matches};
+use crate::utils::string_number;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BalanceChange {
     pub coin_type: String,
-    pub amount: String,
+    #[serde(with = "string_number")]
+    pub amount: i128,
     pub owner: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_details: Option<Owner>,
+    /// Filled in by [`TxParseClient`](crate::TxParseClient) when
+    /// [`ParseOptions::coin_metadata`](crate::ParseOptions::coin_metadata) is
+    /// set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coin_metadata: Option<CoinMetadata>,
+    /// Filled in by
+    /// [`TxParseClient::attach_valuations`](crate::TxParseClient::attach_valuations).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valuation: Option<Valuation>,
+}
+
+impl BalanceChange {
+    /// The amount in whole coins, once metadata with decimals is attached.
+    pub fn coin_amount(&self) -> Option<CoinAmount> {
+        let decimals = self.coin_metadata.as_ref()?.decimals?;
+        Some(CoinAmount::new(self.amount, decimals))
+    }
+
+    pub(crate) fn normalize_addresses(&mut self, format: AddressFormat) {
+        normalize_type(&mut self.coin_type, format);
+        normalize_address(&mut self.owner, format);
+        if let Some(owner) = &mut self.owner_details {
+            owner.normalize_addresses(format);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Owner {
+    Address {
+        address: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Object {
+        object_id: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Shared {
+        #[serde(with = "string_number")]
+        initial_shared_version: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mutable: Option<bool>,
+    },
+    #[serde(rename_all = "camelCase")]
+    ConsensusV2 {
+        #[serde(with = "string_number")]
+        start_version: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        authenticator: Option<Authenticator>,
+    },
+    Immutable,
+    Unknown {
+        value: Value,
+    },
+}
+
+impl Owner {
+    pub(crate) fn normalize_addresses(&mut self, format: AddressFormat) {
+        match self {
+            Owner::Address { address } => normalize_address(address, format),
+            Owner::Object { object_id } => normalize_address(object_id, format),
+            Owner::ConsensusV2 {
+                authenticator: Some(Authenticator::SingleOwner { address }),
+                ..
+            } => normalize_address(address, format),
+            _ => {}
+        }
+    }
+
+    /// Address or object ID for owners that are a single account or object.
+    pub fn address(&self) -> Option<&str> {
+        match self {
+            Owner::Address { address } => Some(address),
+            Owner::Object { object_id } => Some(object_id),
+            _ => None,
+        }
+    }
+}
+
+/// Who may use an object with a `ConsensusV2` owner.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Authenticator {
+    SingleOwner {
+        address: String,
+    },
+    /// An authenticator this version does not know, as the node rendered it.
+    Unknown {
+        value: Value,
+    },
+}
+
+/// Renders the owner label used by `BalanceChange::owner` and the TypeScript
+/// package: the address for address and object owners, `Shared-<version>`,
+/// `ConsensusV2-<version>` and `Immutable`.
+impl fmt::Display for Owner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Owner::Address { address } => f.write_str(address),
+            Owner::Object { object_id } => f.write_str(object_id),
+            Owner::Shared {
+                initial_shared_version,
+                ..
+            } => write!(f, "Shared-{initial_shared_version}"),
+            Owner::ConsensusV2 { start_version, .. } => write!(f, "ConsensusV2-{start_version}"),
+            Owner::Immutable => f.write_str("Immutable"),
+            Owner::Unknown {
+                value: Value::String(value),
+            } => f.write_str(value),
+            Owner::Unknown { value } => write!(f, "{value}"),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DynamicFieldBalanceChange {
+    /// Object holding the balance: the dynamic field itself, or the value
+    /// object of a dynamic object field.
+    pub object_id: String,
+    /// Key of the dynamic field, as rendered by the node.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field_name: Option<Value>,
     pub coin_type: String,
-    pub previous_value: String,
-    pub current_value: String,
-    pub value_diff: String,
+    #[serde(with = "string_number")]
+    pub previous_value: BigUint,
+    #[serde(with = "string_number")]
+    pub current_value: BigUint,
+    #[serde(with = "string_number")]
+    pub value_diff: BigInt,
     pub decimals: u8,
 }
 
+impl DynamicFieldBalanceChange {
+    pub fn previous_amount(&self) -> CoinAmount {
+        CoinAmount::new(self.previous_value.clone(), self.decimals)
+    }
+
+    pub fn current_amount(&self) -> CoinAmount {
+        CoinAmount::new(self.current_value.clone(), self.decimals)
+    }
+
+    pub fn diff_amount(&self) -> CoinAmount {
+        CoinAmount::new(self.value_diff.clone(), self.decimals)
+    }
+}
+
+/// Change of a `Balance<T>` or `Coin<T>` stored in a field of an object.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectBalanceChange {
+    pub object_id: String,
+    /// Dot-separated path to the field, with vector elements by index, e.g.
+    /// `vault.fees` or `rewards[1]`.
+    pub field_path: String,
+    pub coin_type: String,
+    #[serde(with = "string_number")]
+    pub previous_value: BigUint,
+    #[serde(with = "string_number")]
+    pub current_value: BigUint,
+    #[serde(with = "string_number")]
+    pub value_diff: BigInt,
+    pub decimals: u8,
+}
+
+impl ObjectBalanceChange {
+    pub fn previous_amount(&self) -> CoinAmount {
+        CoinAmount::new(self.previous_value.clone(), self.decimals)
+    }
+
+    pub fn current_amount(&self) -> CoinAmount {
+        CoinAmount::new(self.current_value.clone(), self.decimals)
+    }
+
+    pub fn diff_amount(&self) -> CoinAmount {
+        CoinAmount::new(self.value_diff.clone(), self.decimals)
+    }
+}
+
+/// Balance changes found in a container together with the objects that could
+/// not be processed, so callers can tell when the changes are incomplete.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DynamicFieldBalanceReport {
+    pub changes: Vec<DynamicFieldBalanceChange>,
+    pub failures: Vec<ObjectFailure>,
+}
+
+impl DynamicFieldBalanceReport {
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectFailure {
+    pub object_id: String,
+    pub reason: ObjectFailureReason,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ObjectFailureReason {
+    /// The node did not return the requested version, typically because it
+    /// has been pruned. `status` is the raw `sui_tryGetPastObject` status.
+    #[serde(rename_all = "camelCase")]
+    VersionUnavailable { version: String, status: String },
+    /// The node reported a version that is not a decimal u64.
+    #[serde(rename_all = "camelCase")]
+    InvalidVersion { version: String },
+    /// The transaction's effects do not list the version the object had
+    /// before the transaction, so its previous balance cannot be loaded.
+    MissingInputVersion,
+    /// The node found the version but returned it without owner or content.
+    #[serde(rename_all = "camelCase")]
+    MissingDetails { version: String },
+    /// The object is a `Coin` or `Balance` but its amount could not be read.
+    #[serde(rename_all = "camelCase")]
+    UnparsableBalance { object_type: String },
+    /// An amount was rendered as a JSON number that is not a non-negative
+    /// integer, so it cannot be represented exactly.
+    #[serde(rename_all = "camelCase")]
+    InvalidAmount { object_type: String, value: String },
+    /// The object is owned by another object that could not be loaded, so
+    /// it is unknown whether it belongs to the container.
+    #[serde(rename_all = "camelCase")]
+    UnresolvedOwner { owner_id: String },
+    /// Neither the coin metadata nor the decimals registry knows the
+    /// decimals of the object's coin.
+    #[serde(rename_all = "camelCase")]
+    UnknownDecimals { coin_type: String, reason: String },
+    /// Fetching the object or its coin metadata failed.
+    #[serde(rename_all = "camelCase")]
+    Rpc { message: String },
+}
+
+impl fmt::Display for ObjectFailureReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjectFailureReason::VersionUnavailable { version, status } => {
+                write!(f, "version {version} unavailable ({status})")
+            }
+            ObjectFailureReason::InvalidVersion { version } => {
+                write!(f, "invalid version {version:?}")
+            }
+            ObjectFailureReason::MissingInputVersion => {
+                f.write_str("input version missing from the transaction effects")
+            }
+            ObjectFailureReason::MissingDetails { version } => {
+                write!(f, "version {version} was returned without details")
+            }
+            ObjectFailureReason::UnparsableBalance { object_type } => {
+                write!(f, "could not read balance of {object_type}")
+            }
+            ObjectFailureReason::InvalidAmount { object_type, value } => {
+                write!(f, "invalid amount {value} in {object_type}")
+            }
+            ObjectFailureReason::UnresolvedOwner { owner_id } => {
+                write!(f, "owner {owner_id} could not be loaded")
+            }
+            ObjectFailureReason::UnknownDecimals { coin_type, reason } => {
+                write!(f, "decimals of {coin_type} are unknown: {reason}")
+            }
+            ObjectFailureReason::Rpc { message } => f.write_str(message),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GasCostSummary {
-    pub computation_cost: String,
-    pub storage_cost: String,
-    pub storage_rebate: String,
-    pub non_refundable_storage_fee: String,
+    #[serde(with = "string_number")]
+    pub computation_cost: u64,
+    #[serde(with = "string_number")]
+    pub storage_cost: u64,
+    #[serde(with = "string_number")]
+    pub storage_rebate: u64,
+    #[serde(with = "string_number")]
+    pub non_refundable_storage_fee: u64,
+}
+
+impl GasCostSummary {
+    /// Net amount charged to the gas owner. Negative when the storage rebate
+    /// exceeds the computation and storage costs.
+    pub fn net_fee(&self) -> i128 {
+        self.computation_cost as i128 + self.storage_cost as i128 - self.storage_rebate as i128
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+pub struct GasData {
+    pub owner: String,
+    #[serde(with = "string_number")]
+    pub price: u64,
+    #[serde(with = "string_number")]
+    pub budget: u64,
+    pub sponsored: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ObjectChange {
+    #[serde(rename_all = "camelCase")]
+    Created {
+        object_id: String,
+        object_type: String,
+        owner: Option<Owner>,
+        #[serde(with = "string_number")]
+        version: u64,
+        digest: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Mutated {
+        object_id: String,
+        object_type: String,
+        owner: Option<Owner>,
+        #[serde(with = "string_number")]
+        version: u64,
+        #[serde(with = "string_number")]
+        previous_version: u64,
+        digest: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Transferred {
+        object_id: String,
+        object_type: String,
+        recipient: Option<Owner>,
+        #[serde(with = "string_number")]
+        version: u64,
+        digest: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Deleted {
+        object_id: String,
+        object_type: String,
+        #[serde(with = "string_number")]
+        version: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    Wrapped {
+        object_id: String,
+        object_type: String,
+        #[serde(with = "string_number")]
+        version: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    Unwrapped {
+        object_id: String,
+        object_type: String,
+        owner: Option<Owner>,
+        #[serde(with = "string_number")]
+        version: u64,
+        digest: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Published {
+        package_id: String,
+        #[serde(with = "string_number")]
+        version: u64,
+        digest: String,
+        modules: Vec<String>,
+    },
+}
+
+impl ObjectChange {
+    /// Object ID of the changed object, or the package ID for publishes.
+    pub fn object_id(&self) -> &str {
+        match self {
+            ObjectChange::Created { object_id, .. }
+            | ObjectChange::Mutated { object_id, .. }
+            | ObjectChange::Transferred { object_id, .. }
+            | ObjectChange::Deleted { object_id, .. }
+            | ObjectChange::Wrapped { object_id, .. }
+            | ObjectChange::Unwrapped { object_id, .. } => object_id,
+            ObjectChange::Published { package_id, .. } => package_id,
+        }
+    }
+
+    pub(crate) fn normalize_addresses(&mut self, format: AddressFormat) {
+        match self {
+            ObjectChange::Created {
+                object_id,
+                object_type,
+                owner,
+                ..
+            }
+            | ObjectChange::Mutated {
+                object_id,
+                object_type,
+                owner,
+                ..
+            }
+            | ObjectChange::Transferred {
+                object_id,
+                object_type,
+                recipient: owner,
+                ..
+            }
+            | ObjectChange::Unwrapped {
+                object_id,
+                object_type,
+                owner,
+                ..
+            } => {
+                normalize_address(object_id, format);
+                normalize_type(object_type, format);
+                if let Some(owner) = owner {
+                    owner.normalize_addresses(format);
+                }
+            }
+            ObjectChange::Deleted {
+                object_id,
+                object_type,
+                ..
+            }
+            | ObjectChange::Wrapped {
+                object_id,
+                object_type,
+                ..
+            } => {
+                normalize_address(object_id, format);
+                normalize_type(object_type, format);
+            }
+            ObjectChange::Published { package_id, .. } => normalize_address(package_id, format),
+        }
+    }
+
+    /// Same matching rules as `TransactionEvent::is_type`; package publishes
+    /// never match.
+    pub fn is_type(&self, object_type: &str) -> bool {
+        self.object_type()
+            .is_some_and(|actual| type_matches(actual, object_type))
+    }
+
+    /// Move type of the changed object; `None` for package publishes.
+    pub fn object_type(&self) -> Option<&str> {
+        match self {
+            ObjectChange::Created { object_type, .. }
+            | ObjectChange::Mutated { object_type, .. }
+            | ObjectChange::Transferred { object_type, .. }
+            | ObjectChange::Deleted { object_type, .. }
+            | ObjectChange::Wrapped { object_type, .. }
+            | ObjectChange::Unwrapped { object_type, .. } => Some(object_type),
+            ObjectChange::Published { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionEvent {
+    pub package_id: String,
+    pub module: String,
+    pub sender: String,
+    pub event_type: String,
+    pub parsed_json: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bcs: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bcs_encoding: Option<String>,
+    pub event_seq: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_ms: Option<String>,
+}
+
+impl TransactionEvent {
+    /// Returns true when the event type matches `event_type`, ignoring address
+    /// padding. A type without generic parameters also matches every
+    /// instantiation of that struct.
+    pub fn is_type(&self, event_type: &str) -> bool {
+        type_matches(&self.event_type, event_type)
+    }
+
+    pub(crate) fn normalize_addresses(&mut self, format: AddressFormat) {
+        normalize_address(&mut self.package_id, format);
+        normalize_address(&mut self.sender, format);
+        normalize_type(&mut self.event_type, format);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveAbort {
+    pub package: String,
+    pub module: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    pub abort_code: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command_index: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionStatus {
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub move_abort: Option<MoveAbort>,
+}
+
+// `remote = "Self"` turns the derives into inherent functions, wrapped by
+// the trait impls below.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(remote = "Self", rename_all = "camelCase")]
 pub struct ParseResult {
     pub balance_changes: Vec<BalanceChange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transfer_balance_changes: Option<Vec<BalanceChange>>,
     pub gas_cost: GasCostSummary,
+    #[serde(default)]
+    pub events: Vec<TransactionEvent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction: Option<ProgrammableTransaction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<ExecutionStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_data: Option<GasData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object_changes: Option<Vec<ObjectChange>>,
+    /// Always `gas_cost.net_fee()`. Results serialized without it, e.g. by
+    /// earlier versions, get it recomputed when deserialized.
+    #[serde(default, with = "string_number")]
+    pub net_gas_fee: i128,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_ms: Option<String>,
+    /// USD value of `net_gas_fee`, filled in by
+    /// [`TxParseClient::attach_valuations`](crate::TxParseClient::attach_valuations).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_fee_valuation: Option<Valuation>,
+}
+
+impl Serialize for ParseResult {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ParseResult::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ParseResult {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut result = ParseResult::deserialize(deserializer)?;
+        result.net_gas_fee = result.gas_cost.net_fee();
+        Ok(result)
+    }
+}
+
+impl ParseResult {
+    /// Rewrites every address, object ID and type string in the result in
+    /// `format`, so that values can be joined against other data sources.
+    pub fn normalize_addresses(&mut self, format: AddressFormat) {
+        for change in self
+            .balance_changes
+            .iter_mut()
+            .chain(self.transfer_balance_changes.iter_mut().flatten())
+        {
+            change.normalize_addresses(format);
+        }
+        for event in &mut self.events {
+            event.normalize_addresses(format);
+        }
+        if let Some(transaction) = &mut self.transaction {
+            transaction.normalize_addresses(format);
+        }
+        if let Some(abort) = self
+            .status
+            .as_mut()
+            .and_then(|status| status.move_abort.as_mut())
+        {
+            normalize_address(&mut abort.package, format);
+        }
+        if let Some(sender) = &mut self.sender {
+            normalize_address(sender, format);
+        }
+        if let Some(gas_data) = &mut self.gas_data {
+            normalize_address(&mut gas_data.owner, format);
+        }
+        for change in self.object_changes.iter_mut().flatten() {
+            change.normalize_addresses(format);
+        }
+    }
+
+    /// Returns true only when the response reported a successful execution.
+    pub fn is_success(&self) -> bool {
+        self.status.as_ref().is_some_and(|status| status.success)
+    }
+
+    pub fn events_of_type<'a>(
+        &'a self,
+        event_type: &'a str,
+    ) -> impl Iterator<Item = &'a TransactionEvent> + 'a {
+        self.events
+            .iter()
+            .filter(move |event| event.is_type(event_type))
+    }
+
+    pub fn events_from_module<'a>(
+        &'a self,
+        package_id: &'a str,
+        module: &'a str,
+    ) -> impl Iterator<Item = &'a TransactionEvent> + 'a {
+        self.events
+            .iter()
+            .filter(move |event| event.package_id == package_id && event.module == module)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -42,7 +639,32 @@ pub(crate) struct TransactionBlockResponse {
     pub digest: Option<String>,
     #[serde(default)]
     pub balance_changes: Vec<RawBalanceChange>,
+    pub transaction: Option<RawTransactionBlock>,
     pub effects: Option<TransactionEffects>,
+    #[serde(default)]
+    pub events: Vec<RawEvent>,
+    pub object_changes: Option<Vec<RawObjectChange>>,
+    pub timestamp_ms: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct RawTransactionBlock {
+    pub data: RawTransactionData,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RawTransactionData {
+    pub transaction: RawTransactionKind,
+    pub sender: String,
+    pub gas_data: RawGasData,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct RawGasData {
+    pub owner: String,
+    pub price: String,
+    pub budget: String,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -53,26 +675,92 @@ pub(crate) struct RawBalanceChange {
     pub owner: Option<ObjectOwner>,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RawEventId {
+    pub event_seq: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RawEvent {
+    pub id: RawEventId,
+    pub package_id: String,
+    pub transaction_module: String,
+    pub sender: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    #[serde(default)]
+    pub parsed_json: Value,
+    pub bcs: Option<String>,
+    pub bcs_encoding: Option<String>,
+    pub timestamp_ms: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RawGasCostSummary {
+    pub computation_cost: String,
+    pub storage_cost: String,
+    pub storage_rebate: String,
+    pub non_refundable_storage_fee: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct RawExecutionStatus {
+    pub status: String,
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub(crate) struct TransactionEffects {
+    pub status: Option<RawExecutionStatus>,
     #[serde(rename = "gasUsed")]
-    pub gas_used: Option<GasCostSummary>,
+    pub gas_used: Option<RawGasCostSummary>,
+    #[serde(default)]
+    pub unwrapped: Vec<RawOwnedObjectRef>,
+    #[serde(default, rename = "modifiedAtVersions")]
+    pub modified_at_versions: Vec<RawModifiedAtVersion>,
+}
+
+/// Input version of an object mutated, deleted or wrapped by a transaction.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RawModifiedAtVersion {
+    pub object_id: String,
+    pub sequence_number: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct RawOwnedObjectRef {
+    pub reference: RawObjectRef,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RawObjectRef {
+    pub object_id: String,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub(crate) struct SharedOwner {
-    pub initial_shared_version: String,
-    #[serde(default, rename = "mutable")]
-    pub _mutable: Option<bool>,
+    #[serde(with = "string_number")]
+    pub initial_shared_version: u64,
+    #[serde(default)]
+    pub mutable: Option<bool>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub(crate) struct ConsensusV2Owner {
-    pub start_version: String,
+    #[serde(with = "string_number")]
+    pub start_version: u64,
+    #[serde(default)]
+    pub authenticator: Option<Value>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 #[serde(untagged)]
+#[allow(clippy::enum_variant_names)]
 pub(crate) enum ObjectOwner {
     Immutable(String),
     AddressOwner {
@@ -97,29 +785,66 @@ pub(crate) enum ObjectOwner {
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct ObjectChangesResponse {
-    pub object_changes: Option<Vec<ObjectChange>>,
+    pub object_changes: Option<Vec<RawObjectChange>>,
+    pub effects: Option<TransactionEffects>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
-pub(crate) enum ObjectChange {
+pub(crate) enum RawObjectChange {
     #[serde(rename_all = "camelCase")]
     Created {
         object_id: String,
+        #[serde(default)]
+        object_type: String,
         version: String,
+        #[serde(default)]
+        digest: String,
         owner: Option<ObjectOwner>,
     },
     #[serde(rename_all = "camelCase")]
     Mutated {
         object_id: String,
+        #[serde(default)]
+        object_type: String,
         version: String,
         previous_version: String,
+        #[serde(default)]
+        digest: String,
         owner: Option<ObjectOwner>,
     },
     #[serde(rename_all = "camelCase")]
+    Transferred {
+        object_id: String,
+        #[serde(default)]
+        object_type: String,
+        version: String,
+        #[serde(default)]
+        digest: String,
+        recipient: Option<ObjectOwner>,
+    },
+    #[serde(rename_all = "camelCase")]
     Deleted {
-        #[allow(dead_code)]
         object_id: String,
+        #[serde(default)]
+        object_type: String,
+        version: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Wrapped {
+        object_id: String,
+        #[serde(default)]
+        object_type: String,
+        version: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Published {
+        package_id: String,
+        version: String,
+        #[serde(default)]
+        digest: String,
+        #[serde(default)]
+        modules: Vec<String>,
     },
     #[serde(other)]
     Other,
@@ -129,12 +854,24 @@ pub(crate) enum ObjectChange {
 #[serde(rename_all = "camelCase")]
 pub(crate) struct PastObjectResponse {
     pub status: String,
+    #[serde(default, deserialize_with = "deserialize_object_details")]
     pub details: Option<ObjectDetails>,
 }
 
+/// `VersionNotFound` entries carry `[objectId, version]` instead of object
+/// data, which must not fail a whole `sui_tryMultiGetPastObjects` response.
+fn deserialize_object_details<'de, D>(deserializer: D) -> Result<Option<ObjectDetails>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<Value>::deserialize(deserializer)?;
+    Ok(value.and_then(|value| ObjectDetails::deserialize(value).ok()))
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct ObjectDetails {
+    pub owner: Option<ObjectOwner>,
     pub content: Option<ObjectContent>,
 }
 
@@ -147,8 +884,88 @@ pub(crate) struct ObjectContent {
     pub fields: Option<Value>,
 }
 
+/// Layout returned by `sui_getNormalizedMoveStruct`.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct RawNormalizedStruct {
+    pub fields: Vec<RawNormalizedField>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct RawNormalizedField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_: Value,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub(crate) struct CoinMetadata {
+pub(crate) struct RawCoinMetadata {
     pub decimals: Option<u8>,
+    pub symbol: Option<String>,
+    pub name: Option<String>,
+    pub icon_url: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(decimals: Option<u8>) -> CoinMetadata {
+        CoinMetadata {
+            decimals,
+            symbol: "USDC".to_string(),
+            name: "USD Coin".to_string(),
+            icon_url: None,
+        }
+    }
+
+    #[test]
+    fn converts_balance_changes_once_decimals_are_known() {
+        let mut change = BalanceChange {
+            coin_type: "0xdba3::usdc::USDC".to_string(),
+            amount: -1_250_000,
+            owner: "0x5905".to_string(),
+            owner_details: None,
+            coin_metadata: None,
+            valuation: None,
+        };
+        assert_eq!(change.coin_amount(), None);
+
+        change.coin_metadata = Some(metadata(None));
+        assert_eq!(change.coin_amount(), None);
+
+        change.coin_metadata = Some(metadata(Some(6)));
+        let amount = change.coin_amount().expect("decimals are known");
+        assert_eq!(amount, CoinAmount::new(-1_250_000, 6));
+        assert_eq!(amount.to_string(), "-1.250000");
+    }
+
+    #[test]
+    fn converts_field_balances_with_their_decimals() {
+        let change = DynamicFieldBalanceChange {
+            object_id: "0xf1e1".to_string(),
+            field_name: None,
+            coin_type: "0x2::sui::SUI".to_string(),
+            previous_value: BigUint::from(1_500_000_000u64),
+            current_value: BigUint::from(250_000_000u64),
+            value_diff: BigInt::from(-1_250_000_000i64),
+            decimals: 9,
+        };
+        assert_eq!(change.previous_amount().to_string(), "1.500000000");
+        assert_eq!(change.current_amount().to_string(), "0.250000000");
+        assert_eq!(change.diff_amount(), CoinAmount::new(-1_250_000_000i64, 9));
+
+        let change = ObjectBalanceChange {
+            object_id: "0xa001".to_string(),
+            field_path: "vault.fees".to_string(),
+            coin_type: change.coin_type,
+            previous_value: BigUint::default(),
+            current_value: BigUint::from(7u8),
+            value_diff: BigInt::from(7),
+            decimals: 2,
+        };
+        assert_eq!(change.previous_amount().to_string(), "0.00");
+        assert_eq!(change.current_amount().to_string(), "0.07");
+        assert_eq!(change.diff_amount(), CoinAmount::new(7, 2));
+    }
 }
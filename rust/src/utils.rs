@@ -1,16 +1,94 @@
-use crate::types::ObjectOwner;
+use serde_json::Value;
 
-pub(crate) fn get_actual_owner(owner: Option<&ObjectOwner>) -> Option<String> {
-    let owner = owner?;
-
-    match owner {
-        ObjectOwner::Immutable(value) => Some(value.clone()),
-        ObjectOwner::AddressOwner { address_owner } => Some(address_owner.clone()),
-        ObjectOwner::ObjectOwner { object_owner } => Some(object_owner.clone()),
-        ObjectOwner::Shared { shared } => Some(format!("Shared-{}", shared.initial_shared_version)),
-        ObjectOwner::ConsensusV2 { consensus_v2 } => {
-            Some(format!("ConsensusV2-{}", consensus_v2.start_version))
+use crate::types::{Authenticator, ObjectOwner, Owner};
+
+pub(crate) const SUI_COIN_TYPE: &str = "0x2::sui::SUI";
+
+/// Accepts both the short (`0x2::sui::SUI`) and the zero-padded form.
+pub(crate) fn is_sui_coin_type(coin_type: &str) -> bool {
+    coin_type
+        .strip_suffix("::sui::SUI")
+        .and_then(|address| address.strip_prefix("0x"))
+        .is_some_and(|address| address.trim_start_matches('0') == "2")
+}
+
+impl From<&ObjectOwner> for Owner {
+    fn from(owner: &ObjectOwner) -> Self {
+        match owner {
+            ObjectOwner::Immutable(value) if value == "Immutable" => Owner::Immutable,
+            ObjectOwner::Immutable(value) => Owner::Unknown {
+                value: Value::String(value.clone()),
+            },
+            ObjectOwner::AddressOwner { address_owner } => Owner::Address {
+                address: address_owner.clone(),
+            },
+            ObjectOwner::ObjectOwner { object_owner } => Owner::Object {
+                object_id: object_owner.clone(),
+            },
+            ObjectOwner::Shared { shared } => Owner::Shared {
+                initial_shared_version: shared.initial_shared_version,
+                mutable: shared.mutable,
+            },
+            ObjectOwner::ConsensusV2 { consensus_v2 } => Owner::ConsensusV2 {
+                start_version: consensus_v2.start_version,
+                authenticator: consensus_v2.authenticator.as_ref().map(Authenticator::from),
+            },
+            ObjectOwner::Other(value) => Owner::Unknown {
+                value: value.clone(),
+            },
         }
-        ObjectOwner::Other(value) => Some(value.to_string()),
+    }
+}
+
+impl From<&Value> for Authenticator {
+    fn from(authenticator: &Value) -> Self {
+        match authenticator.get("SingleOwner").and_then(Value::as_str) {
+            Some(address) => Authenticator::SingleOwner {
+                address: address.to_string(),
+            },
+            None => Authenticator::Unknown {
+                value: authenticator.clone(),
+            },
+        }
+    }
+}
+
+pub(crate) fn get_actual_owner(owner: Option<&ObjectOwner>) -> Option<String> {
+    owner.map(|owner| Owner::from(owner).to_string())
+}
+
+/// Serializes integers as decimal strings, matching the Sui JSON-RPC encoding
+/// and the TypeScript package, while accepting either strings or numbers.
+pub(crate) mod string_number {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNumber {
+        String(String),
+        Number(serde_json::Number),
+    }
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Display,
+        S: Serializer,
+    {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        let raw = match StringOrNumber::deserialize(deserializer)? {
+            StringOrNumber::String(value) => value,
+            StringOrNumber::Number(value) => value.to_string(),
+        };
+        raw.parse().map_err(serde::de::Error::custom)
     }
 }